@@ -1 +1,33 @@
+pub mod archive;
+pub mod estimates;
+pub mod export;
+pub mod git;
+pub mod import;
+pub mod merge;
+pub mod merge_driver;
+pub mod overview;
+pub mod recurrence;
+pub mod runner;
 pub mod tasks;
+pub mod taskwarrior;
+pub mod time_tracking;
+pub mod todos;
+
+use crate::{
+    files::storage,
+    structs::{Command, Data},
+};
+
+
+// Runs a subcommand from the command line instead of the TUI
+pub fn run(command: Command, data: &mut Data)
+{
+    match command
+    {
+        Command::Archive { command } => archive::run(command, &mut data.archive),
+        Command::Tasks { command } => tasks::run(command, data),
+        Command::Export(command) => export::run(command, data),
+        Command::Import(command) => import::run(command, data),
+        Command::MigrateStorage { to } => storage::migrate_storage(to),
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+
+use chrono::{DateTime, Local, TimeDelta};
+use ratatui::widgets::ListState;
+
+use crate::{
+    commands::{
+        merge,
+        tasks::{Task, Tasks},
+    },
+    files,
+    structs::Status,
+};
+
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ArchiveCli
+{
+    /// List the archived tasks
+    List,
+
+    /// Remove archived tasks that are older than the given number of days
+    Purge
+    {
+        #[arg(long, value_name = "DAYS")]
+        older_than: u32,
+    },
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Default)]
+pub struct Archive
+{
+    #[serde(default)]
+    pub entries: Vec<ArchivedTask>,
+
+    #[serde(skip)]
+    pub list_state: ListState,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+pub struct ArchivedTask
+{
+    pub archived_at: DateTime<Local>,
+    pub reason: ArchiveReason,
+    pub task: Task,
+}
+
+// When an entry was archived and the id of its task, together they tell the entries apart
+pub type ArchiveKey = (DateTime<Local>, u64);
+
+// Why a task left the task list
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+pub enum ArchiveReason
+{
+    Completed,
+    Cancelled,
+    Deleted,
+}
+
+impl fmt::Display for ArchiveReason
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let s = match self
+        {
+            ArchiveReason::Completed => "Completed",
+            ArchiveReason::Cancelled => "Cancelled",
+            ArchiveReason::Deleted => "Deleted",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl Archive
+{
+    // Returns the keys of the new entries, what undo needs to find them again
+    pub fn add(&mut self, tasks: Vec<Task>, reason: ArchiveReason) -> Vec<ArchiveKey>
+    {
+        let now: DateTime<Local> = Local::now();
+        let keys: Vec<ArchiveKey> = tasks.iter().map(|task| (now, task.id)).collect();
+        self.entries.extend(tasks.into_iter().map(|task| ArchivedTask { archived_at: now, reason: reason.clone(), task }));
+        keys
+    }
+
+    // Undoing a change takes the tasks it archived back out. Entries restored or purged since are already gone
+    pub fn remove_entries(&mut self, keys: &[ArchiveKey])
+    {
+        self.entries.retain(|entry| !keys.contains(&(entry.archived_at, entry.task.id)));
+    }
+
+    // Indices of the entries whose task matches the search, newest first
+    pub fn search(&self, query: &str) -> Vec<usize>
+    {
+        let query: String = query.to_lowercase();
+        (0..self.entries.len())
+            .rev()
+            .filter(|index|
+            {
+                let task: &Task = &self.entries[*index].task;
+                query.is_empty()
+                    || task.task.to_lowercase().contains(&query)
+                    || task.description.to_lowercase().contains(&query)
+                    || task.tags.iter().any(|tag| tag.to_lowercase().contains(&query))
+                    || task.project.as_ref().is_some_and(|project| project.to_lowercase().contains(&query))
+            })
+            .collect()
+    }
+
+    // Takes an entry out of the archive, finished tasks come back as todo
+    pub fn restore(&mut self, index: usize) -> Task
+    {
+        let mut task: Task = self.entries.remove(index).task;
+        if task.is_finished()
+        {
+            task.status = Status::Todo;
+        }
+        task
+    }
+
+    pub fn purge_older_than(&mut self, days: u32) -> usize
+    {
+        let cutoff: DateTime<Local> = Local::now() - TimeDelta::days(days as i64);
+        let before: usize = self.entries.len();
+        self.entries.retain(|entry| entry.archived_at >= cutoff);
+        before - self.entries.len()
+    }
+}
+
+// Moves the done and cancelled tasks into the archive, as part of the last undo step
+pub fn archive_finished(tasks: &mut Tasks, archive: &mut Archive)
+{
+    let finished: Vec<Task> = tasks.take_finished();
+    let (completed, cancelled): (Vec<Task>, Vec<Task>) = finished.into_iter()
+        .partition(|task| task.status == Status::Done);
+    let mut keys: Vec<ArchiveKey> = archive.add(completed, ArchiveReason::Completed);
+    keys.extend(archive.add(cancelled, ArchiveReason::Cancelled));
+    tasks.record_archived(keys);
+}
+
+pub fn archive_deleted(tasks: &mut Tasks, archive: &mut Archive, deleted: Vec<Task>)
+{
+    let keys: Vec<ArchiveKey> = archive.add(deleted, ArchiveReason::Deleted);
+    tasks.record_archived(keys);
+}
+
+pub fn run(command: ArchiveCli, archive: &mut Archive)
+{
+    match command
+    {
+        ArchiveCli::List =>
+        {
+            for entry in &archive.entries
+            {
+                println!("{}  {:<9}  {}", entry.archived_at.format("%Y-%m-%d %H:%M"), entry.reason.to_string(), entry.task.task);
+            }
+        }
+        ArchiveCli::Purge { older_than } =>
+        {
+            let purged: usize = archive.purge_older_than(older_than);
+            write_archive(archive);
+            println!("Purged {} archived tasks older than {} days", purged, older_than);
+        }
+    }
+}
+
+// Writing the archive to the file, and replacing the whole file
+pub fn write_archive(archive: &mut Archive)
+{
+    if files::shared()
+    {
+        merge::merge_archive_with_disk(archive);
+    }
+
+    let toml_str = toml::to_string_pretty(&archive)
+        .expect("Failed to serialize archive");
+
+    files::write_file("archive", toml_str);
+}
@@ -0,0 +1,237 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use chrono::{DateTime, Local};
+
+use crate::commands::{
+    archive::{Archive, ArchiveReason},
+    tasks::Task,
+};
+
+
+// A guess of the work in a task, written as text like "2.5h" or "3pt"
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub enum Estimate
+{
+    Hours(f64),
+    Points(u32),
+}
+
+impl fmt::Display for Estimate
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            Estimate::Hours(hours) => write!(f, "{}h", hours),
+            Estimate::Points(points) => write!(f, "{}pt", points),
+        }
+    }
+}
+
+impl FromStr for Estimate
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let input: String = s.trim().to_lowercase();
+        let error = || format!("'{}' is not an estimate, use hours like 2.5h or points like 3pt", s.trim());
+
+        if let Some(hours) = input.strip_suffix('h')
+        {
+            return match hours.trim().parse::<f64>()
+            {
+                Ok(hours) if hours > 0.0 && hours.is_finite() => Ok(Estimate::Hours(hours)),
+                _ => Err(error()),
+            };
+        }
+        let points: &str = input.strip_suffix("pt").or_else(|| input.strip_suffix("sp")).ok_or_else(error)?;
+        match points.trim().parse::<u32>()
+        {
+            Ok(points) if points > 0 => Ok(Estimate::Points(points)),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl TryFrom<String> for Estimate
+{
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error>
+    {
+        value.parse()
+    }
+}
+
+impl From<Estimate> for String
+{
+    fn from(value: Estimate) -> Self
+    {
+        value.to_string()
+    }
+}
+
+// Estimates against what it really took, for one tag or priority
+#[derive(Debug, Default, Clone)]
+pub struct AccuracyRow
+{
+    pub group: String,
+    pub tasks: usize,
+    pub estimated_hours: f64,
+    pub tracked_hours: f64,
+    pub points: u32,
+    // Days between creating and completing the tasks with points
+    pub days: f64,
+}
+
+impl AccuracyRow
+{
+    // Above 1 means the work took longer than estimated
+    pub fn hours_ratio(&self) -> Option<f64>
+    {
+        (self.estimated_hours > 0.0).then(|| self.tracked_hours / self.estimated_hours)
+    }
+
+    pub fn days_per_point(&self) -> Option<f64>
+    {
+        (self.points > 0 && self.days > 0.0).then(|| self.days / self.points as f64)
+    }
+
+    fn add(&mut self, task: &Task, completed_at: DateTime<Local>)
+    {
+        self.tasks += 1;
+        match task.estimate
+        {
+            // Without tracked time there is nothing to hold the estimate against
+            Some(Estimate::Hours(hours)) =>
+            {
+                let tracked: i64 = task.tracked_time(completed_at).num_seconds();
+                if tracked > 0
+                {
+                    self.estimated_hours += hours;
+                    self.tracked_hours += tracked as f64 / 3600.0;
+                }
+            }
+            // Likewise the points only count when it is known how long the task took
+            Some(Estimate::Points(points)) =>
+            {
+                if let Some(created) = task.created
+                {
+                    self.points += points;
+                    self.days += (completed_at - created).num_seconds().max(0) as f64 / 86400.0;
+                }
+            }
+            None => {},
+        }
+    }
+}
+
+pub struct AccuracyReport
+{
+    pub by_tag: Vec<AccuracyRow>,
+    pub by_priority: Vec<AccuracyRow>,
+}
+
+// Looks at the completed tasks in the archive that had an estimate
+pub fn accuracy_report(archive: &Archive) -> AccuracyReport
+{
+    let mut by_tag: BTreeMap<String, AccuracyRow> = BTreeMap::new();
+    let mut by_priority: BTreeMap<String, AccuracyRow> = BTreeMap::new();
+
+    let completed = archive.entries.iter()
+        .filter(|entry| entry.reason == ArchiveReason::Completed && entry.task.estimate.is_some());
+    for entry in completed
+    {
+        let tags: Vec<String> = if entry.task.tags.is_empty()
+        {
+            vec!["(untagged)".to_string()]
+        } else {
+            entry.task.tags.clone()
+        };
+        for tag in tags
+        {
+            by_tag.entry(tag.clone())
+                .or_insert_with(|| AccuracyRow { group: tag, ..Default::default() })
+                .add(&entry.task, entry.archived_at);
+        }
+        let priority: String = entry.task.priority.to_string();
+        by_priority.entry(priority.clone())
+            .or_insert_with(|| AccuracyRow { group: priority, ..Default::default() })
+            .add(&entry.task, entry.archived_at);
+    }
+
+    AccuracyReport
+    {
+        by_tag: by_tag.into_values().collect(),
+        by_priority: by_priority.into_values().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use chrono::{TimeDelta, TimeZone};
+
+    use super::*;
+    use crate::{commands::time_tracking::TimeEntry, structs::Priority};
+
+    fn time(day: u32, hour: u32) -> DateTime<Local>
+    {
+        Local.with_ymd_and_hms(2025, 1, day, hour, 0, 0).unwrap()
+    }
+
+    fn task(estimate: &str) -> Task
+    {
+        let mut task: Task = Task::new("Task".to_string(), Priority::Medium, String::new());
+        task.estimate = Some(estimate.parse().unwrap());
+        task
+    }
+
+    #[test]
+    fn parses_estimates()
+    {
+        assert_eq!("2.5h".parse::<Estimate>(), Ok(Estimate::Hours(2.5)));
+        assert_eq!(" 3PT ".parse::<Estimate>(), Ok(Estimate::Points(3)));
+        assert_eq!("5sp".parse::<Estimate>(), Ok(Estimate::Points(5)));
+        assert_eq!(Estimate::Hours(1.5).to_string(), "1.5h");
+        for invalid in ["", "3", "0h", "-1h", "infh", "0pt", "1.5pt", "three hours"]
+        {
+            assert!(invalid.parse::<Estimate>().is_err(), "{} parsed", invalid);
+        }
+    }
+
+    #[test]
+    fn hours_only_count_with_tracked_time()
+    {
+        let mut row: AccuracyRow = AccuracyRow::default();
+        row.add(&task("2h"), time(2, 12));
+        assert_eq!(row.tasks, 1);
+        assert_eq!(row.hours_ratio(), None);
+
+        let mut tracked: Task = task("2h");
+        tracked.time_entries.push(TimeEntry { start: time(2, 9), end: Some(time(2, 12)) });
+        row.add(&tracked, time(2, 12));
+        assert_eq!(row.tasks, 2);
+        assert_eq!(row.estimated_hours, 2.0);
+        assert_eq!(row.tracked_hours, 3.0);
+        assert_eq!(row.hours_ratio(), Some(1.5));
+    }
+
+    #[test]
+    fn points_only_count_with_a_creation_time()
+    {
+        let mut row: AccuracyRow = AccuracyRow::default();
+        row.add(&task("3pt"), time(10, 12));
+        assert_eq!(row.points, 0);
+        assert_eq!(row.days_per_point(), None);
+
+        let mut created: Task = task("3pt");
+        created.created = Some(time(10, 12) - TimeDelta::days(6));
+        row.add(&created, time(10, 12));
+        assert_eq!(row.tasks, 2);
+        assert_eq!(row.points, 3);
+        assert_eq!(row.days_per_point(), Some(2.0));
+    }
+}
@@ -0,0 +1,368 @@
+use std::{
+    fs,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Local, NaiveDate};
+
+use crate::{
+    commands::{
+        estimates::Estimate,
+        self,
+        recurrence::Recurrence,
+        taskwarrior,
+        tasks::{Task, Tasks},
+        time_tracking::TimeEntry,
+    },
+    files::storage::{self, TaskQuery},
+    structs::{Data, Priority, Status},
+};
+
+
+#[derive(Debug, clap::Args)]
+pub struct ExportCli
+{
+    #[arg(long, value_enum, default_value = "markdown")]
+    pub format: ExportFormat,
+
+    #[command(flatten)]
+    pub query: TaskQuery,
+
+    /// The order of the list when not given
+    #[arg(long, value_enum)]
+    pub sort: Option<TaskSort>,
+
+    /// How the Markdown checklist is split up
+    #[arg(long, value_enum, default_value = "status")]
+    pub group_by: GroupBy,
+
+    /// Written to stdout when not given
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ExportFormat
+{
+    Markdown,
+    Csv,
+    Json,
+    // The JSON that `task import` reads
+    Taskwarrior,
+}
+
+impl ExportFormat
+{
+    pub fn extension(&self) -> &'static str
+    {
+        match self
+        {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Taskwarrior => "taskwarrior.json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum TaskSort
+{
+    Id,
+    Title,
+    Priority,
+    Status,
+    // Tasks without a due date go last
+    Due,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum GroupBy
+{
+    Status,
+    Priority,
+}
+
+// The JSON schema. Every field is always there, so scripts do not have to guess
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct ExportFile
+{
+    pub version: u32,
+    pub tasks: Vec<ExportedTask>,
+}
+
+// Bumped when a field changes meaning or goes away, new fields keep the version
+pub const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct ExportedTask
+{
+    pub id: u64,
+    pub title: String,
+    pub description: String,
+    pub status: Status,
+    pub priority: Priority,
+    pub tags: Vec<String>,
+    pub project: Option<String>,
+    pub due: Option<NaiveDate>,
+    pub recurrence: Option<Recurrence>,
+    pub estimate: Option<Estimate>,
+    pub created: Option<DateTime<Local>>,
+    pub time_entries: Vec<TimeEntry>,
+    pub pomodoros: u32,
+    pub commits: Vec<String>,
+    pub branches: Vec<String>,
+    pub source: Option<String>,
+    #[serde(default)]
+    pub uuid: Option<String>,
+}
+
+impl From<&Task> for ExportedTask
+{
+    fn from(task: &Task) -> Self
+    {
+        Self
+        {
+            id: task.id,
+            title: task.task.clone(),
+            description: task.description.clone(),
+            status: task.status.clone(),
+            priority: task.priority.clone(),
+            tags: task.tags.clone(),
+            project: task.project.clone(),
+            due: task.due,
+            recurrence: task.recurrence.clone(),
+            estimate: task.estimate.clone(),
+            created: task.created,
+            time_entries: task.time_entries.clone(),
+            pomodoros: task.pomodoros,
+            commits: task.commits.clone(),
+            branches: task.branches.clone(),
+            source: task.source.clone(),
+            uuid: task.uuid.clone(),
+        }
+    }
+}
+
+// The id is left out, an imported task gets a new one
+impl From<ExportedTask> for Task
+{
+    fn from(exported: ExportedTask) -> Self
+    {
+        Self
+        {
+            tags: exported.tags,
+            project: exported.project,
+            due: exported.due,
+            recurrence: exported.recurrence,
+            estimate: exported.estimate,
+            created: exported.created,
+            time_entries: exported.time_entries,
+            pomodoros: exported.pomodoros,
+            commits: exported.commits,
+            branches: exported.branches,
+            source: exported.source,
+            uuid: exported.uuid,
+            status: exported.status,
+            ..Task::new(exported.title, exported.priority, exported.description)
+        }
+    }
+}
+
+pub fn run(command: ExportCli, data: &mut Data)
+{
+    let mut tasks: Vec<Task> = storage::with(|storage| storage.query_tasks(&command.query))
+        .unwrap_or_else(|error| storage::refuse(error));
+    if let Some(sort) = command.sort
+    {
+        sort_tasks(&mut tasks, sort);
+    }
+    if command.format == ExportFormat::Taskwarrior
+        && let Some(all) = data.tasks.as_mut()
+        && ensure_uuids(&mut tasks, all)
+    {
+        commands::tasks::write_tasks(all);
+    }
+
+    let content: String = export(&tasks, command.format, command.group_by);
+    match command.output
+    {
+        Some(path) =>
+        {
+            if let Err(error) = fs::write(&path, content)
+            {
+                eprintln!("Could not write {}: {}", path.display(), error);
+                std::process::exit(1);
+            }
+            eprintln!("Exported {} tasks to {}", tasks.len(), path.display());
+        }
+        None => print!("{}", content),
+    }
+}
+
+pub fn export(tasks: &[Task], format: ExportFormat, group_by: GroupBy) -> String
+{
+    match format
+    {
+        ExportFormat::Markdown => to_markdown(tasks, group_by),
+        ExportFormat::Csv => to_csv(tasks),
+        ExportFormat::Json => to_json(tasks),
+        ExportFormat::Taskwarrior => taskwarrior::to_json(tasks),
+    }
+}
+
+// Tasks sent to Taskwarrior keep the UUID they got, so importing them back updates them.
+// Returns whether any task got a new one
+pub fn ensure_uuids(exported: &mut [Task], tasks: &mut Tasks) -> bool
+{
+    let mut changed: bool = false;
+    for task in exported.iter_mut().filter(|task| task.uuid.is_none())
+    {
+        let uuid: String = commands::tasks::new_uuid();
+        if let Some(index) = tasks.find(task.id)
+        {
+            tasks.tasks[index].uuid = Some(uuid.clone());
+            changed = true;
+        }
+        task.uuid = Some(uuid);
+    }
+    changed
+}
+
+// Stable, so tasks that are equal keep the order of the list
+pub fn sort_tasks(tasks: &mut [Task], sort: TaskSort)
+{
+    match sort
+    {
+        TaskSort::Id => tasks.sort_by_key(|task| task.id),
+        TaskSort::Title => tasks.sort_by_key(|task| task.task.to_lowercase()),
+        TaskSort::Priority => tasks.sort_by_key(|task| priority_rank(&task.priority)),
+        TaskSort::Status => tasks.sort_by_key(|task| status_rank(&task.status)),
+        TaskSort::Due => tasks.sort_by_key(|task| (task.due.is_none(), task.due)),
+    }
+}
+
+fn priority_rank(priority: &Priority) -> u8
+{
+    match priority
+    {
+        Priority::High => 0,
+        Priority::Medium => 1,
+        Priority::Low => 2,
+    }
+}
+
+fn status_rank(status: &Status) -> u8
+{
+    match status
+    {
+        Status::InProgress => 0,
+        Status::Todo => 1,
+        Status::Done => 2,
+        Status::Cancelled => 3,
+    }
+}
+
+// A checklist per group, ready to paste into a PR
+fn to_markdown(tasks: &[Task], group_by: GroupBy) -> String
+{
+    let groups: Vec<(String, Vec<&Task>)> = match group_by
+    {
+        GroupBy::Status =>
+        {
+            [Status::InProgress, Status::Todo, Status::Done, Status::Cancelled].into_iter()
+                .map(|status| (status.to_string(), tasks.iter().filter(|task| task.status == status).collect()))
+                .collect()
+        }
+        GroupBy::Priority =>
+        {
+            [Priority::High, Priority::Medium, Priority::Low].into_iter()
+                .map(|priority| (priority.to_string(), tasks.iter().filter(|task| task.priority == priority).collect()))
+                .collect()
+        }
+    };
+
+    let mut markdown: String = String::new();
+    for (title, group) in groups.into_iter().filter(|(_, group)| !group.is_empty())
+    {
+        if !markdown.is_empty()
+        {
+            markdown.push('\n');
+        }
+        markdown.push_str(&format!("## {}\n\n", title));
+        for task in group
+        {
+            let check: &str = if matches!(task.status, Status::Done | Status::Cancelled) { "x" } else { " " };
+            let title: String = if task.status == Status::Cancelled { format!("~~{}~~", task.task) } else { task.task.clone() };
+
+            let mut details: Vec<String> = vec![format!("#{}", task.id)];
+            match group_by
+            {
+                GroupBy::Status => details.push(task.priority.to_string()),
+                GroupBy::Priority => details.push(task.status.to_string()),
+            }
+            details.extend(task.project.as_ref().map(|project| format!("project {}", project)));
+            details.extend(task.due.map(|due| format!("due {}", due)));
+            details.extend(task.tags.iter().map(|tag| format!("`{}`", tag)));
+            markdown.push_str(&format!("- [{}] {} ({})\n", check, title, details.join(", ")));
+        }
+    }
+    markdown
+}
+
+pub const CSV_HEADER: [&str; 17] = [
+    "id", "title", "description", "status", "priority", "tags", "project", "due",
+    "recurrence", "estimate", "created", "tracked_minutes", "pomodoros", "commits", "branches", "source", "uuid",
+];
+
+// Lists inside a field are split by ';'
+fn to_csv(tasks: &[Task]) -> String
+{
+    let now: DateTime<Local> = Local::now();
+    let mut csv: String = CSV_HEADER.join(",");
+    csv.push('\n');
+    for task in tasks
+    {
+        let fields: [String; 17] = [
+            task.id.to_string(),
+            task.task.clone(),
+            task.description.clone(),
+            task.status.to_string(),
+            task.priority.to_string(),
+            task.tags.join(";"),
+            task.project.clone().unwrap_or_default(),
+            task.due.map(|due| due.to_string()).unwrap_or_default(),
+            task.recurrence.as_ref().map(|recurrence| recurrence.to_string()).unwrap_or_default(),
+            task.estimate.as_ref().map(|estimate| estimate.to_string()).unwrap_or_default(),
+            task.created.map(|created| created.to_rfc3339()).unwrap_or_default(),
+            task.tracked_time(now).num_minutes().to_string(),
+            task.pomodoros.to_string(),
+            task.commits.join(";"),
+            task.branches.join(";"),
+            task.source.clone().unwrap_or_default(),
+            task.uuid.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String
+{
+    if field.contains([',', '"', '\n', '\r'])
+    {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn to_json(tasks: &[Task]) -> String
+{
+    let file: ExportFile = ExportFile { version: EXPORT_VERSION, tasks: tasks.iter().map(ExportedTask::from).collect() };
+    let mut json: String = serde_json::to_string_pretty(&file).unwrap_or_default();
+    json.push('\n');
+    json
+}
@@ -0,0 +1,286 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::files;
+
+
+// What the git screen shows, read from the local repository only
+#[derive(Debug, Default, Clone)]
+pub struct GitStatus
+{
+    pub branch: String,
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    // Status code and path, like "M " and "src/main.rs"
+    pub dirty: Vec<(String, String)>,
+    pub stashes: usize,
+    pub commits: Vec<CommitInfo>,
+    pub branches: Vec<BranchInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CommitInfo
+{
+    pub hash: String,
+    pub author: String,
+    pub date: String,
+    pub subject: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct BranchInfo
+{
+    pub name: String,
+    pub current: bool,
+}
+
+// The directory that holds .dashboard, git looks upwards from there for the repository
+pub fn project_dir() -> PathBuf
+{
+    let base_path: PathBuf = files::base_path();
+    base_path.parent().map(Path::to_path_buf).unwrap_or(base_path)
+}
+
+// Runs a git command in the repository and returns its output
+pub fn git(repo: &Path, args: &[&str]) -> Result<String, String>
+{
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .map_err(|error| format!("Could not run git: {}", error))?;
+
+    if !output.status.success()
+    {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn read_status(repo: &Path, commit_count: usize) -> Result<GitStatus, String>
+{
+    let mut status: GitStatus = GitStatus::default();
+
+    let porcelain: String = git(repo, &["status", "--porcelain=v1", "--branch"])?;
+    for line in porcelain.lines()
+    {
+        match line.strip_prefix("## ")
+        {
+            Some(branch_line) => parse_branch_line(branch_line, &mut status),
+            None if line.len() > 3 => status.dirty.push((line[..2].to_string(), line[3..].to_string())),
+            None => {},
+        }
+    }
+
+    status.stashes = git(repo, &["stash", "list"]).map(|stashes| stashes.lines().count()).unwrap_or(0);
+
+    // A repository without commits has no log yet
+    let log_count: String = format!("-n{}", commit_count);
+    let log: String = git(repo, &["log", &log_count, "--format=%h%x1f%an%x1f%ar%x1f%s"]).unwrap_or_default();
+    status.commits = log.lines()
+        .filter_map(|line|
+        {
+            let mut fields = line.split('\x1f');
+            Some(CommitInfo
+            {
+                hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+            })
+        })
+        .collect();
+
+    let branches: String = git(repo, &["branch", "--format=%(HEAD)%(refname:short)"])?;
+    status.branches = branches.lines()
+        .map(|line| BranchInfo { current: line.starts_with('*'), name: line[1..].trim().to_string() })
+        .collect();
+
+    Ok(status)
+}
+
+// Lines like "main...origin/main [ahead 1, behind 2]", "No commits yet on main" or "HEAD (no branch)"
+fn parse_branch_line(line: &str, status: &mut GitStatus)
+{
+    let (names, tracking) = match line.split_once(" [")
+    {
+        Some((names, tracking)) => (names, tracking.trim_end_matches(']')),
+        None => (line, ""),
+    };
+    let names: &str = names.strip_prefix("No commits yet on ").unwrap_or(names);
+    match names.split_once("...")
+    {
+        Some((branch, upstream)) =>
+        {
+            status.branch = branch.to_string();
+            status.upstream = Some(upstream.to_string());
+        }
+        None => status.branch = names.to_string(),
+    }
+
+    for part in tracking.split(", ")
+    {
+        if let Some(ahead) = part.strip_prefix("ahead ")
+        {
+            status.ahead = ahead.parse().unwrap_or(0);
+        } else if let Some(behind) = part.strip_prefix("behind ") {
+            status.behind = behind.parse().unwrap_or(0);
+        }
+    }
+}
+
+// Words that make a commit close the task it mentions, like "fixes #42"
+const FIX_WORDS: [&str; 9] = ["fix", "fixes", "fixed", "close", "closes", "closed", "resolve", "resolves", "resolved"];
+
+// A commit that mentions task ids, like "task #42", "[#42]", "[T-42]" or "fixes #42". A bare "#42" is
+// left alone, that is how GitHub refers to pull requests and issues
+#[derive(Debug, Clone)]
+pub struct CommitLink
+{
+    pub hash: String,
+    pub subject: String,
+    pub tasks: Vec<u64>,
+    pub fixes: Vec<u64>,
+}
+
+// A branch with a task id in its name, like "T-42-login-page"
+#[derive(Debug, Clone)]
+pub struct BranchLink
+{
+    pub name: String,
+    pub tasks: Vec<u64>,
+}
+
+// The commits the local branches point at
+pub fn branch_heads(repo: &Path) -> Result<Vec<String>, String>
+{
+    let heads: String = git(repo, &["for-each-ref", "--format=%(objectname)", "refs/heads"])?;
+    Ok(heads.lines().map(str::to_string).collect())
+}
+
+// Scans the history of the given heads, leaving out what was already seen from the heads of an earlier scan
+pub fn scan_commit_links(repo: &Path, heads: &[String], seen: &[String]) -> Result<Vec<CommitLink>, String>
+{
+    if heads.is_empty()
+    {
+        return Ok(Vec::new());
+    }
+    let excluded: Vec<String> = seen.iter().map(|head| format!("^{}", head)).collect();
+    let mut args: Vec<&str> = vec!["log", "--format=%H%x1f%s%x1f%b%x1e"];
+    args.extend(heads.iter().map(String::as_str));
+    args.extend(excluded.iter().map(String::as_str));
+    args.push("--");
+
+    let log: String = git(repo, &args)?;
+    let links: Vec<CommitLink> = log.split('\x1e')
+        .filter_map(|commit|
+        {
+            let mut fields = commit.trim_start().split('\x1f');
+            let hash: String = fields.next()?.to_string();
+            let subject: String = fields.next()?.to_string();
+            let message: String = format!("{}\n{}", subject, fields.next().unwrap_or_default()).to_lowercase();
+
+            let mut link: CommitLink = CommitLink { hash, subject, tasks: Vec::new(), fixes: Vec::new() };
+            for (start, id) in task_references(&message)
+            {
+                if !link.tasks.contains(&id)
+                {
+                    link.tasks.push(id);
+                }
+                if FIX_WORDS.contains(&word_before(&message, start)) && !link.fixes.contains(&id)
+                {
+                    link.fixes.push(id);
+                }
+            }
+            Some(link).filter(|link| !link.tasks.is_empty())
+        })
+        .collect();
+    Ok(links)
+}
+
+// Where each reference to a task starts in a lowercase commit message, in the order they appear.
+// A bare "#42" only counts right after a fix word
+fn task_references(message: &str) -> Vec<(usize, u64)>
+{
+    let mut references: Vec<(usize, u64)> = ids_after(message, "task #");
+    references.extend(ids_after(message, "[#"));
+    references.extend(ids_after(message, "[t-"));
+    references.extend(ids_after(message, "#").into_iter().filter(|(start, _)| FIX_WORDS.contains(&word_before(message, *start))));
+    references.sort();
+    references
+}
+
+// The word in front of a position, without the colon of "fixes: #42"
+fn word_before(message: &str, position: usize) -> &str
+{
+    message[..position].split_whitespace().next_back().unwrap_or_default().trim_end_matches(':')
+}
+
+pub fn scan_branch_links(repo: &Path) -> Result<Vec<BranchLink>, String>
+{
+    let branches: String = git(repo, &["branch", "--format=%(refname:short)"])?;
+    let links: Vec<BranchLink> = branches.lines()
+        .filter_map(|name|
+        {
+            let mut tasks: Vec<u64> = ids_after(name, "T-").into_iter().map(|(_, id)| id).collect();
+            tasks.extend(ids_after(name, "t-").into_iter().map(|(_, id)| id));
+            Some(BranchLink { name: name.to_string(), tasks }).filter(|link| !link.tasks.is_empty())
+        })
+        .collect();
+    Ok(links)
+}
+
+// The position and id of every prefix followed by a number, when the prefix does not start inside a word
+fn ids_after(text: &str, prefix: &str) -> Vec<(usize, u64)>
+{
+    text.match_indices(prefix)
+        .filter_map(|(start, _)|
+        {
+            if text[..start].chars().next_back().is_some_and(char::is_alphanumeric)
+            {
+                return None;
+            }
+            let digits: String = text[start + prefix.len()..].chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().ok().filter(|id| *id != 0).map(|id| (start, id))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn ids_after_skips_prefixes_inside_words()
+    {
+        assert_eq!(ids_after("T-4 and T-12", "T-"), [(0, 4), (8, 12)]);
+        assert_eq!(ids_after("feature/T-7-login", "T-"), [(8, 7)]);
+        assert_eq!(ids_after("PT-3 T-0 T-x T-", "T-"), []);
+    }
+
+    #[test]
+    fn only_dashboard_references_count()
+    {
+        let ids = |message: &str| -> Vec<u64> { task_references(message).into_iter().map(|(_, id)| id).collect() };
+        assert_eq!(ids("merge pull request #5 from someone/branch"), Vec::<u64>::new());
+        assert_eq!(ids("see issue #12"), Vec::<u64>::new());
+        assert_eq!(ids("fixes task #3, [#4] and [t-5]"), [3, 4, 5]);
+        assert_eq!(ids("fixes #42, closes: #43 and mentions #44"), [42, 43]);
+        assert_eq!(ids("[t-9] start the login page (task #2)"), [9, 2]);
+    }
+
+    #[test]
+    fn parses_the_branch_line()
+    {
+        let mut status: GitStatus = GitStatus::default();
+        parse_branch_line("main...origin/main [ahead 1, behind 2]", &mut status);
+        assert_eq!(status.branch, "main");
+        assert_eq!(status.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((status.ahead, status.behind), (1, 2));
+    }
+}
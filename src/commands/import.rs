@@ -0,0 +1,660 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, NaiveDate};
+
+use crate::{
+    commands::{
+        self,
+        export::{self, ExportFile},
+        taskwarrior,
+        tasks::Task,
+    },
+    structs::{Data, Priority, Status},
+};
+
+
+#[derive(Debug, clap::Args)]
+pub struct ImportCli
+{
+    /// The file to read, `-` for stdin
+    pub file: PathBuf,
+
+    /// Guessed from the extension when not given
+    #[arg(long, value_enum)]
+    pub format: Option<ImportFormat>,
+
+    /// Show what would be imported without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Which task field a CSV column fills, like `--map "Summary=title"`
+    #[arg(long = "map", value_name = "COLUMN=FIELD", value_parser = parse_mapping)]
+    pub mappings: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ImportFormat
+{
+    TodoTxt,
+    Markdown,
+    Csv,
+    Json,
+    // What `task export` writes
+    Taskwarrior,
+}
+
+// The task fields a CSV column can fill
+const CSV_FIELDS: [&str; 12] = [
+    "title", "description", "status", "priority", "tags", "project", "due", "recurrence", "estimate", "created", "source", "uuid",
+];
+
+fn parse_mapping(mapping: &str) -> Result<(String, String), String>
+{
+    let Some((column, field)) = mapping.split_once('=') else
+    {
+        return Err("expected COLUMN=FIELD".to_string());
+    };
+    let field: String = field.trim().to_lowercase();
+    if !CSV_FIELDS.contains(&field.as_str())
+    {
+        return Err(format!("unknown field `{}`, pick one of {}", field, CSV_FIELDS.join(", ")));
+    }
+    Ok((column.trim().to_string(), field))
+}
+
+pub fn run(command: ImportCli, data: &mut Data)
+{
+    let content: String = if command.file.as_os_str() == "-"
+    {
+        let mut content: String = String::new();
+        io::stdin().read_to_string(&mut content).unwrap_or_else(|error| fail(error.to_string()));
+        content
+    } else {
+        fs::read_to_string(&command.file)
+            .unwrap_or_else(|error| fail(format!("Could not read {}: {}", command.file.display(), error)))
+    };
+    let Some(format) = command.format.or_else(|| guess_format(&command.file, &content)) else
+    {
+        fail(format!("Can not tell the format of {}, pick one with --format", command.file.display()));
+    };
+
+    let parsed: Result<Vec<Task>, String> = match format
+    {
+        ImportFormat::TodoTxt => Ok(parse_todo_txt(&content)),
+        ImportFormat::Markdown => Ok(parse_markdown(&content)),
+        ImportFormat::Csv => parse_csv(&content, &command.mappings),
+        ImportFormat::Json => parse_json(&content),
+        ImportFormat::Taskwarrior => taskwarrior::parse(&content),
+    };
+    let parsed: Vec<Task> = parsed.unwrap_or_else(|error| fail(error));
+
+    // Never write over tasks that could not be read
+    let Some(tasks) = data.tasks.as_mut() else
+    {
+        fail("The current tasks could not be read, fix them before importing".to_string());
+    };
+
+    let (updates, new, duplicates) = sort_out(parsed, &tasks.tasks);
+    let updates: Vec<(usize, Task)> = updates.into_iter()
+        .map(|(index, task)| (index, updated(&tasks.tasks[index], task)))
+        .filter(|(index, task)| toml::Value::try_from(task).ok() != toml::Value::try_from(&tasks.tasks[*index]).ok())
+        .collect();
+    for task in &new
+    {
+        println!("+ {}", describe(task));
+    }
+    for (_, task) in &updates
+    {
+        println!("~ {}", describe(task));
+    }
+    for task in &duplicates
+    {
+        println!("= {} (already there)", task.task);
+    }
+
+    if command.dry_run
+    {
+        println!("Dry run: {} tasks would be imported and {} updated, {} duplicates skipped", new.len(), updates.len(), duplicates.len());
+        return;
+    }
+    let (count, updated_count): (usize, usize) = (new.len(), updates.len());
+    for (index, task) in updates
+    {
+        tasks.tasks[index] = task;
+    }
+    for mut task in new
+    {
+        task.id = 0;
+        tasks.add(task);
+    }
+    commands::tasks::write_tasks(tasks);
+    println!("Imported {} tasks and updated {}, {} duplicates skipped", count, updated_count, duplicates.len());
+}
+
+fn fail(message: String) -> !
+{
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+// Taskwarrior exports are a list, the exports of this dashboard an object
+fn guess_format(path: &Path, content: &str) -> Option<ImportFormat>
+{
+    let extension: String = path.extension()?.to_str()?.to_lowercase();
+    match extension.as_str()
+    {
+        "txt" => Some(ImportFormat::TodoTxt),
+        "md" | "markdown" => Some(ImportFormat::Markdown),
+        "csv" => Some(ImportFormat::Csv),
+        "json" if content.trim_start().starts_with('[') => Some(ImportFormat::Taskwarrior),
+        "json" => Some(ImportFormat::Json),
+        _ => None,
+    }
+}
+
+// Tasks with the UUID of a task that is there update it, the rest is new or a duplicate by title.
+// Titles are compared without case and extra spaces, against the tasks and the rest of the import.
+// A task with a UUID that has the title of a task without one is linked to that task
+fn sort_out(imported: Vec<Task>, existing: &[Task]) -> (Vec<(usize, Task)>, Vec<Task>, Vec<Task>)
+{
+    let normalize = |title: &str| title.split_whitespace().collect::<Vec<&str>>().join(" ").to_lowercase();
+    let mut seen: BTreeSet<String> = existing.iter().map(|task| normalize(&task.task)).collect();
+    let (mut updates, mut new, mut duplicates): (Vec<(usize, Task)>, Vec<Task>, Vec<Task>) = (Vec::new(), Vec::new(), Vec::new());
+    for task in imported.into_iter().filter(|task| !task.task.trim().is_empty())
+    {
+        let by_uuid: Option<usize> = task.uuid.as_ref()
+            .and_then(|uuid| existing.iter().position(|other| other.uuid.as_ref() == Some(uuid)));
+        let by_title = ||
+        {
+            task.uuid.as_ref()?;
+            existing.iter().position(|other| other.uuid.is_none() && normalize(&other.task) == normalize(&task.task))
+        };
+        if let Some(index) = by_uuid.or_else(by_title)
+        {
+            updates.push((index, task));
+        } else if seen.insert(normalize(&task.task)) {
+            new.push(task);
+        } else {
+            duplicates.push(task);
+        }
+    }
+    (updates, new, duplicates)
+}
+
+// What the import knows replaces what the task had, what only the dashboard tracks stays
+fn updated(existing: &Task, imported: Task) -> Task
+{
+    Task
+    {
+        id: existing.id,
+        created: existing.created.or(imported.created),
+        recurrence: existing.recurrence.clone().or(imported.recurrence),
+        estimate: existing.estimate.clone().or(imported.estimate),
+        time_entries: existing.time_entries.clone(),
+        pomodoros: existing.pomodoros,
+        commits: existing.commits.clone(),
+        branches: existing.branches.clone(),
+        source: existing.source.clone().or(imported.source),
+        ..imported
+    }
+}
+
+fn describe(task: &Task) -> String
+{
+    let mut details: Vec<String> = vec![task.status.to_string(), task.priority.to_string()];
+    details.extend(task.project.as_ref().map(|project| format!("project {}", project)));
+    details.extend(task.due.map(|due| format!("due {}", due)));
+    details.extend(task.tags.iter().map(|tag| format!("#{}", tag)));
+    format!("{} ({})", task.task, details.join(", "))
+}
+
+pub fn parse_priority(value: &str) -> Option<Priority>
+{
+    match value.trim().to_lowercase().as_str()
+    {
+        "high" | "h" | "a" | "1" => Some(Priority::High),
+        "medium" | "m" | "b" | "2" | "normal" => Some(Priority::Medium),
+        "low" | "l" | "c" | "3" => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+pub fn parse_status(value: &str) -> Option<Status>
+{
+    match value.trim().to_lowercase().replace(['_', '-'], " ").as_str()
+    {
+        "todo" | "to do" | "open" | "pending" | "" => Some(Status::Todo),
+        "in progress" | "inprogress" | "doing" | "started" | "active" => Some(Status::InProgress),
+        "done" | "completed" | "complete" | "closed" | "finished" | "x" => Some(Status::Done),
+        "cancelled" | "canceled" | "deleted" | "wontfix" | "won't fix" => Some(Status::Cancelled),
+        _ => None,
+    }
+}
+
+// Takes 2025-01-31 as well as a full timestamp
+pub fn parse_date(value: &str) -> Option<NaiveDate>
+{
+    let value: &str = value.trim();
+    NaiveDate::parse_from_str(value.get(..10).unwrap_or(value), "%Y-%m-%d").ok()
+}
+
+// `x 2025-01-31 2025-01-01 (A) Title +project @context due:2025-02-01`, everything but the title is optional
+fn parse_todo_txt(content: &str) -> Vec<Task>
+{
+    let mut tasks: Vec<Task> = Vec::new();
+    for line in content.lines().map(str::trim).filter(|line| !line.is_empty())
+    {
+        let mut words = line.split_whitespace().peekable();
+        let mut task: Task = Task::new(String::new(), Priority::Medium, String::new());
+
+        if words.peek() == Some(&"x")
+        {
+            words.next();
+            task.status = Status::Done;
+        }
+        let mut dates: Vec<NaiveDate> = Vec::new();
+        while let Some(word) = words.peek()
+        {
+            if let Some(letter) = word.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')).filter(|letter| letter.len() == 1)
+            {
+                task.priority = parse_priority(letter).unwrap_or(Priority::Low);
+            } else if let Some(date) = parse_date(word).filter(|_| word.len() == 10) {
+                dates.push(date);
+            } else {
+                break;
+            }
+            words.next();
+        }
+        // A done task has its completion date first, the last date is when it was made
+        task.created = dates.last()
+            .filter(|_| task.status != Status::Done || dates.len() == 2)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .and_then(|date| date.and_local_timezone(Local).single());
+
+        let mut title: Vec<&str> = Vec::new();
+        for word in words
+        {
+            if let Some(project) = word.strip_prefix('+').filter(|project| !project.is_empty())
+            {
+                match task.project
+                {
+                    None => task.project = Some(project.to_string()),
+                    Some(_) => task.add_tag(project),
+                }
+            } else if let Some(context) = word.strip_prefix('@').filter(|context| !context.is_empty()) {
+                task.add_tag(context);
+            } else if let Some(due) = word.strip_prefix("due:").and_then(parse_date) {
+                task.due = Some(due);
+            } else if let Some(priority) = word.strip_prefix("pri:").and_then(parse_priority) {
+                task.priority = priority;
+            } else {
+                title.push(word);
+            }
+        }
+        task.task = title.join(" ");
+        tasks.push(task);
+    }
+    tasks
+}
+
+// `- [ ]` and `- [x]` items. Headings named after a status or priority apply to the items under them,
+// and the details the Markdown export puts behind a title are read back
+fn parse_markdown(content: &str) -> Vec<Task>
+{
+    let mut tasks: Vec<Task> = Vec::new();
+    let (mut heading_status, mut heading_priority): (Option<Status>, Option<Priority>) = (None, None);
+    for line in content.lines().map(str::trim)
+    {
+        if let Some(heading) = line.strip_prefix('#').map(|heading| heading.trim_start_matches('#').trim())
+        {
+            heading_status = parse_status(heading).filter(|_| !heading.is_empty());
+            heading_priority = parse_priority(heading).filter(|_| heading.len() > 1);
+            continue;
+        }
+
+        let item: &str = line.trim_start_matches(|c: char| c.is_ascii_digit());
+        let item: &str = item.strip_prefix(['-', '*', '+', '.', ')']).unwrap_or(item).trim_start();
+        let (checked, title): (bool, &str) = if let Some(title) = item.strip_prefix("[ ]")
+        {
+            (false, title.trim())
+        } else if let Some(title) = item.strip_prefix("[x]").or_else(|| item.strip_prefix("[X]")) {
+            (true, title.trim())
+        } else {
+            continue;
+        };
+        if title.is_empty() || item.len() == line.len()
+        {
+            continue;
+        }
+
+        let mut task: Task = Task::new(title.to_string(), heading_priority.clone().unwrap_or(Priority::Medium), String::new());
+        task.status = match (checked, heading_status.clone())
+        {
+            (false, Some(status)) if !matches!(status, Status::Done | Status::Cancelled) => status,
+            (false, _) => Status::Todo,
+            (true, _) => Status::Done,
+        };
+        read_export_details(&mut task);
+        if let Some(title) = task.task.strip_prefix("~~").and_then(|title| title.strip_suffix("~~"))
+        {
+            task.task = title.to_string();
+            task.status = Status::Cancelled;
+        }
+        tasks.push(task);
+    }
+    tasks
+}
+
+// `Title (#3, High, project web, due 2025-01-31, `tag`)`, left in the title when it is not all understood
+fn read_export_details(task: &mut Task)
+{
+    let Some((title, details)) = task.task.rsplit_once(" (#").and_then(|(title, details)| Some((title, details.strip_suffix(')')?))) else
+    {
+        return;
+    };
+    let mut parsed: Task = task.clone();
+    parsed.task = title.trim().to_string();
+    for (index, part) in details.split(", ").enumerate()
+    {
+        if index == 0
+        {
+            if part.parse::<u64>().is_err()
+            {
+                return;
+            }
+        } else if let Some(project) = part.strip_prefix("project ") {
+            parsed.project = Some(project.to_string());
+        } else if let Some(due) = part.strip_prefix("due ").and_then(parse_date) {
+            parsed.due = Some(due);
+        } else if let Some(tag) = part.strip_prefix('`').and_then(|tag| tag.strip_suffix('`')) {
+            parsed.add_tag(tag);
+        } else if let Some(priority) = parse_priority(part).filter(|_| part.len() > 1) {
+            parsed.priority = priority;
+        } else if let Some(status) = parse_status(part).filter(|_| part.len() > 1) {
+            // The checkbox already tells whether it is finished
+            if !parsed.is_finished()
+            {
+                parsed.status = status;
+            }
+        } else {
+            return;
+        }
+    }
+    *task = parsed;
+}
+
+// Columns are matched to fields by name, the mappings come first
+fn parse_csv(content: &str, mappings: &[(String, String)]) -> Result<Vec<Task>, String>
+{
+    let mut rows = read_csv(content).into_iter();
+    let Some(header) = rows.next() else
+    {
+        return Ok(Vec::new());
+    };
+
+    let fields: Vec<Option<&str>> = header.iter()
+        .map(|column|
+        {
+            mappings.iter()
+                .find(|(mapped, _)| mapped.eq_ignore_ascii_case(column.trim()))
+                .map(|(_, field)| field.as_str())
+                .or_else(|| csv_field_for(column))
+        })
+        .collect();
+    if !fields.contains(&Some("title"))
+    {
+        return Err(format!("No column holds the title, map one with --map \"COLUMN=title\". The columns are: {}", header.join(", ")));
+    }
+    let ignored: Vec<&str> = header.iter().zip(&fields)
+        .filter(|(_, field)| field.is_none())
+        .map(|(column, _)| column.as_str())
+        .collect();
+    if !ignored.is_empty()
+    {
+        eprintln!("Ignoring the columns {}", ignored.join(", "));
+    }
+
+    let mut tasks: Vec<Task> = Vec::new();
+    for (number, row) in rows.enumerate()
+    {
+        let mut task: Task = Task::new(String::new(), Priority::Medium, String::new());
+        for (value, field) in row.iter().zip(&fields).filter(|(value, _)| !value.trim().is_empty())
+        {
+            let value: &str = value.trim();
+            let line: usize = number + 2;
+            match field
+            {
+                Some("title") => task.task = value.to_string(),
+                Some("description") => task.description = value.to_string(),
+                Some("status") => task.status = parse_status(value).ok_or(format!("Line {}: unknown status `{}`", line, value))?,
+                Some("priority") => task.priority = parse_priority(value).ok_or(format!("Line {}: unknown priority `{}`", line, value))?,
+                Some("tags") =>
+                {
+                    for tag in value.split([';', ',']).map(str::trim).filter(|tag| !tag.is_empty())
+                    {
+                        task.add_tag(tag);
+                    }
+                }
+                Some("project") => task.project = Some(value.to_string()),
+                Some("due") => task.due = Some(parse_date(value).ok_or(format!("Line {}: `{}` is not a date", line, value))?),
+                Some("recurrence") => task.recurrence = Some(value.parse().map_err(|error| format!("Line {}: {}", line, error))?),
+                Some("estimate") => task.estimate = Some(value.parse().map_err(|error| format!("Line {}: {}", line, error))?),
+                Some("created") => task.created = DateTime::parse_from_rfc3339(value).ok().map(|created| created.with_timezone(&Local)),
+                Some("source") => task.source = Some(value.to_string()),
+                Some("uuid") => task.uuid = Some(value.to_string()),
+                _ => {},
+            }
+        }
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+// The names other tools tend to give these columns, the export of this tool included
+fn csv_field_for(column: &str) -> Option<&'static str>
+{
+    let column: String = column.trim().to_lowercase().replace([' ', '-'], "_");
+    let field: &'static str = match column.as_str()
+    {
+        "title" | "task" | "name" | "summary" | "subject" => "title",
+        "description" | "notes" | "note" | "details" | "body" => "description",
+        "status" | "state" => "status",
+        "priority" | "prio" => "priority",
+        "tags" | "tag" | "labels" | "label" | "contexts" => "tags",
+        "project" => "project",
+        "due" | "due_date" | "deadline" => "due",
+        "recurrence" | "repeat" => "recurrence",
+        "estimate" => "estimate",
+        "created" | "created_at" | "entry" => "created",
+        "source" => "source",
+        "uuid" => "uuid",
+        _ => return None,
+    };
+    Some(field)
+}
+
+// Quoted fields can hold commas, newlines and doubled quotes
+fn read_csv(content: &str) -> Vec<Vec<String>>
+{
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field: String = String::new();
+    let mut quoted: bool = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next()
+    {
+        match c
+        {
+            '"' if quoted && chars.peek() == Some(&'"') =>
+            {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {},
+            '\n' if !quoted =>
+            {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty()
+    {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|row| row.iter().any(|field| !field.trim().is_empty()));
+    rows
+}
+
+fn parse_json(content: &str) -> Result<Vec<Task>, String>
+{
+    let file: ExportFile = serde_json::from_str(content).map_err(|error| format!("Not an export of this dashboard: {}", error))?;
+    if file.version > export::EXPORT_VERSION
+    {
+        return Err(format!("The export is version {}, but this dashboard only reads up to version {}", file.version, export::EXPORT_VERSION));
+    }
+    Ok(file.tasks.into_iter().map(Task::from).collect())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::commands::export::{ExportFormat, GroupBy};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate
+    {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn task(title: &str) -> Task
+    {
+        Task::new(title.to_string(), Priority::Medium, String::new())
+    }
+
+    #[test]
+    fn reads_quoted_csv_fields()
+    {
+        let rows: Vec<Vec<String>> = read_csv("title,notes\r\n\"Say \"\"hi\"\", then\",\"two\nlines\"\n\n,\nlast,");
+        assert_eq!(rows, [
+            vec!["title".to_string(), "notes".to_string()],
+            vec!["Say \"hi\", then".to_string(), "two\nlines".to_string()],
+            vec!["last".to_string(), String::new()],
+        ]);
+    }
+
+    #[test]
+    fn parses_todo_txt()
+    {
+        let tasks: Vec<Task> = parse_todo_txt("
+            (A) 2025-01-02 Call Mom +family +phone @home due:2025-01-10
+            x 2025-01-05 2025-01-01 Pay rent pri:L
+            x 2025-01-05 Without a creation date
+        ");
+        assert_eq!(tasks.len(), 3);
+
+        assert_eq!(tasks[0].task, "Call Mom");
+        assert_eq!(tasks[0].priority, Priority::High);
+        assert_eq!(tasks[0].project.as_deref(), Some("family"));
+        assert_eq!(tasks[0].tags, ["phone", "home"]);
+        assert_eq!(tasks[0].due, Some(date(2025, 1, 10)));
+        assert_eq!(tasks[0].created.map(|created| created.date_naive()), Some(date(2025, 1, 2)));
+
+        assert_eq!(tasks[1].task, "Pay rent");
+        assert_eq!(tasks[1].status, Status::Done);
+        assert_eq!(tasks[1].priority, Priority::Low);
+        assert_eq!(tasks[1].created.map(|created| created.date_naive()), Some(date(2025, 1, 1)));
+
+        assert_eq!(tasks[2].status, Status::Done);
+        assert_eq!(tasks[2].created, None);
+    }
+
+    #[test]
+    fn parses_markdown_checklists()
+    {
+        let tasks: Vec<Task> = parse_markdown("
+            # Plan
+            - [ ] Plain item
+            * [x] Done item
+            ## In Progress
+            1. [ ] Started item
+            ## High
+            - [ ] Urgent item
+            - [x] ~~Dropped item~~
+            - not a task
+            [ ] no list marker
+        ");
+        let summary: Vec<(&str, Status, Priority)> = tasks.iter()
+            .map(|task| (task.task.as_str(), task.status.clone(), task.priority.clone()))
+            .collect();
+        assert_eq!(summary, [
+            ("Plain item", Status::Todo, Priority::Medium),
+            ("Done item", Status::Done, Priority::Medium),
+            ("Started item", Status::InProgress, Priority::Medium),
+            ("Urgent item", Status::Todo, Priority::High),
+            ("Dropped item", Status::Cancelled, Priority::High),
+        ]);
+    }
+
+    #[test]
+    fn reads_back_the_markdown_export()
+    {
+        let mut exported: Task = task("Ship it (v2)");
+        exported.id = 3;
+        exported.priority = Priority::Low;
+        exported.project = Some("web".to_string());
+        exported.due = Some(date(2025, 1, 31));
+        exported.tags = vec!["release".to_string()];
+
+        let tasks: Vec<Task> = parse_markdown(&export::export(&[exported], ExportFormat::Markdown, GroupBy::Status));
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].task, "Ship it (v2)");
+        assert_eq!(tasks[0].priority, Priority::Low);
+        assert_eq!(tasks[0].project.as_deref(), Some("web"));
+        assert_eq!(tasks[0].due, Some(date(2025, 1, 31)));
+        assert_eq!(tasks[0].tags, ["release"]);
+    }
+
+    #[test]
+    fn csv_columns_are_found_by_name_or_mapping()
+    {
+        let content: &str = "Summary,Labels,Deadline,Effort\nWrite docs,docs;web,2025-02-01,3pt\n";
+        let tasks: Vec<Task> = parse_csv(content, &[("effort".to_string(), "estimate".to_string())]).unwrap();
+        assert_eq!(tasks[0].task, "Write docs");
+        assert_eq!(tasks[0].tags, ["docs", "web"]);
+        assert_eq!(tasks[0].due, Some(date(2025, 2, 1)));
+        assert!(tasks[0].estimate.is_some());
+
+        assert!(parse_csv("Effort\n3pt\n", &[]).is_err());
+        assert!(parse_csv("title,due\nTask,someday\n", &[]).unwrap_err().starts_with("Line 2"));
+    }
+
+    #[test]
+    fn sorts_out_updates_new_tasks_and_duplicates()
+    {
+        let mut linked: Task = task("Linked");
+        linked.uuid = Some("a".to_string());
+        let existing: Vec<Task> = vec![linked, task("Old title"), task("Existing")];
+
+        let mut renamed: Task = task("New title");
+        renamed.uuid = Some("a".to_string());
+        let mut from_taskwarrior: Task = task("old  TITLE");
+        from_taskwarrior.uuid = Some("b".to_string());
+        let imported: Vec<Task> = vec![renamed, from_taskwarrior, task("existing"), task("Fresh"), task("fresh"), task("  ")];
+
+        let (updates, new, duplicates) = sort_out(imported, &existing);
+        let updates: Vec<(usize, &str)> = updates.iter().map(|(index, task)| (*index, task.task.as_str())).collect();
+        assert_eq!(updates, [(0, "New title"), (1, "old  TITLE")]);
+        assert_eq!(new.iter().map(|task| task.task.as_str()).collect::<Vec<_>>(), ["Fresh"]);
+        assert_eq!(duplicates.iter().map(|task| task.task.as_str()).collect::<Vec<_>>(), ["existing", "fresh"]);
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::BTreeSet;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    commands::{
+        archive::{Archive, ArchivedTask},
+        runner::{RunHistory, RunRecord},
+        tasks::{Task, Tasks},
+    },
+    files::{self, storage},
+};
+
+
+// Three-way merge of task lists by id, field by field.
+// A field changed on one side takes that change, when both changed it mine wins.
+// Deleting a task loses from changing it, and both sides adding the same id keeps both tasks.
+// Tasks that need a new id come back with id 0, so `Tasks::assign_ids` has to run afterwards
+pub fn merge_tasks(base: &[Task], mine: &[Task], theirs: &[Task]) -> Vec<Task>
+{
+    merge_tasks_with_conflicts(base, mine, theirs).0
+}
+
+// A task that both sides changed the same fields of, in different ways
+pub struct TaskConflict
+{
+    pub id: u64,
+    pub fields: Vec<String>,
+    // The merged task with their side of the conflicting fields, the merged list has mine
+    pub theirs: Task,
+}
+
+// Like `merge_tasks`, but also tells which tasks had fields changed on both sides
+pub fn merge_tasks_with_conflicts(base: &[Task], mine: &[Task], theirs: &[Task]) -> (Vec<Task>, Vec<TaskConflict>)
+{
+    let find = |list: &[Task], id: u64| list.iter().find(|task| task.id == id).cloned();
+    let mut merged: Vec<Task> = Vec::new();
+    let mut renumbered: Vec<Task> = Vec::new();
+    let mut conflicts: Vec<TaskConflict> = Vec::new();
+
+    // Mine decides the order, their new tasks go at the end
+    for task in mine
+    {
+        match (find(base, task.id), find(theirs, task.id))
+        {
+            (Some(base_task), Some(their_task)) =>
+            {
+                let (merged_task, conflict) = merge_task(&base_task, task, &their_task);
+                merged.push(merged_task);
+                conflicts.extend(conflict);
+            }
+            (Some(base_task), None) if same(&base_task, task) => {},
+            (None, Some(their_task)) if !same(task, &their_task) =>
+            {
+                merged.push(task.clone());
+                renumbered.push(Task { id: 0, ..their_task });
+            }
+            _ => merged.push(task.clone()),
+        }
+    }
+    for task in theirs.iter().filter(|task| find(mine, task.id).is_none())
+    {
+        match find(base, task.id)
+        {
+            Some(base_task) if same(&base_task, task) => {},
+            _ => merged.push(task.clone()),
+        }
+    }
+    merged.extend(renumbered);
+    (merged, conflicts)
+}
+
+// The merged task with mine winning, and the conflict when both sides changed a field differently
+pub fn merge_task(base: &Task, mine: &Task, theirs: &Task) -> (Task, Option<TaskConflict>)
+{
+    let (Some(base_table), Some(mine_table), Some(theirs_table)) = (to_table(base), to_table(mine), to_table(theirs)) else
+    {
+        return (mine.clone(), None);
+    };
+
+    let mut merged: toml::Table = toml::Table::new();
+    let mut fields: Vec<String> = Vec::new();
+    let keys = mine_table.keys().chain(theirs_table.keys()).chain(base_table.keys());
+    for key in keys
+    {
+        if merged.contains_key(key) || fields.contains(key)
+        {
+            continue;
+        }
+        let value: Option<&toml::Value> = if mine_table.get(key) == base_table.get(key)
+        {
+            theirs_table.get(key)
+        } else {
+            if theirs_table.get(key) != base_table.get(key) && theirs_table.get(key) != mine_table.get(key)
+            {
+                fields.push(key.clone());
+            }
+            mine_table.get(key)
+        };
+        if let Some(value) = value
+        {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+
+    let Ok(merged_task) = toml::Value::Table(merged.clone()).try_into::<Task>() else
+    {
+        return (mine.clone(), None);
+    };
+    if fields.is_empty()
+    {
+        return (merged_task, None);
+    }
+
+    for key in &fields
+    {
+        match theirs_table.get(key)
+        {
+            Some(value) => merged.insert(key.clone(), value.clone()),
+            None => merged.remove(key),
+        };
+    }
+    let conflict: Option<TaskConflict> = toml::Value::Table(merged).try_into::<Task>().ok()
+        .map(|their_task| TaskConflict { id: merged_task.id, fields, theirs: their_task });
+    (merged_task, conflict)
+}
+
+fn to_table(task: &Task) -> Option<toml::Table>
+{
+    match toml::Value::try_from(task)
+    {
+        Ok(toml::Value::Table(table)) => Some(table),
+        _ => None,
+    }
+}
+
+fn same(a: &Task, b: &Task) -> bool
+{
+    to_table(a) == to_table(b)
+}
+
+// Merges lists that only grow, like the archive. Their entries stay unless mine took them out,
+// and the entries only mine has are added after them
+pub fn merge_entries<T, K, F>(base: &[T], mine: Vec<T>, theirs: Vec<T>, key: F) -> Vec<T>
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    let base_keys: BTreeSet<K> = base.iter().map(&key).collect();
+    let mine_keys: BTreeSet<K> = mine.iter().map(&key).collect();
+    let theirs_keys: BTreeSet<K> = theirs.iter().map(&key).collect();
+
+    let mut merged: Vec<T> = theirs.into_iter()
+        .filter(|entry| !base_keys.contains(&key(entry)) || mine_keys.contains(&key(entry)))
+        .collect();
+    merged.extend(mine.into_iter()
+        .filter(|entry| !base_keys.contains(&key(entry)) && !theirs_keys.contains(&key(entry))));
+    merged
+}
+
+// Puts the merge of the tasks with theirs in place, marks point at the old order so they go
+pub fn apply_merge(tasks: &mut Tasks, base: &[Task], theirs: &Tasks)
+{
+    tasks.tasks = merge_tasks(base, &tasks.tasks, &theirs.tasks);
+    tasks.next_id = tasks.next_id.max(theirs.next_id);
+    tasks.assign_ids();
+    tasks.clear_marks();
+    tasks.clamp_selection();
+}
+
+// When another dashboard has the lock, every save first merges with what it wrote.
+// The file on disk is theirs, and what we last read or wrote is the base
+pub fn merge_tasks_with_disk(tasks: &mut Tasks)
+{
+    let Some((theirs, base)) = changed_on_disk("tasks", storage::read_tasks()) else
+    {
+        return;
+    };
+    apply_merge(tasks, &base.tasks, &theirs);
+}
+
+pub fn merge_archive_with_disk(archive: &mut Archive)
+{
+    let Some((theirs, base)) = changed_on_disk::<Archive>("archive", files::read_toml("archive")) else
+    {
+        return;
+    };
+    let mine: Vec<ArchivedTask> = std::mem::take(&mut archive.entries);
+    archive.entries = merge_entries(&base.entries, mine, theirs.entries, |entry| (entry.archived_at, entry.task.id));
+    archive.entries.sort_by_key(|entry| entry.archived_at);
+}
+
+pub fn merge_runs_with_disk(history: &mut RunHistory)
+{
+    let Some((theirs, base)) = changed_on_disk::<RunHistory>("runs", files::read_toml("runs")) else
+    {
+        return;
+    };
+    let mine: Vec<RunRecord> = std::mem::take(&mut history.runs);
+    history.runs = merge_entries(&base.runs, mine, theirs.runs, |run| (run.started, run.command.clone()));
+    history.runs.sort_by_key(|run| run.started);
+}
+
+// What is on disk and the base, when it changed since we last read or wrote it
+fn changed_on_disk<T>(name: &str, theirs: Option<T>) -> Option<(T, T)>
+where
+    T: DeserializeOwned + Serialize + Default,
+{
+    let theirs: T = theirs?;
+    let synced: String = files::synced(name).unwrap_or_default();
+    if toml::to_string_pretty(&theirs).is_ok_and(|content| content == synced)
+    {
+        return None;
+    }
+    let base: T = toml::from_str(&synced).unwrap_or_default();
+    Some((theirs, base))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::structs::{Priority, Status};
+
+    fn task(id: u64, title: &str) -> Task
+    {
+        Task { id, ..Task::new(title.to_string(), Priority::Medium, String::new()) }
+    }
+
+    fn titles(tasks: &[Task]) -> Vec<(u64, &str)>
+    {
+        tasks.iter().map(|task| (task.id, task.task.as_str())).collect()
+    }
+
+    #[test]
+    fn merge_task_takes_the_fields_each_side_changed()
+    {
+        let base: Task = task(1, "Write docs");
+        let mine: Task = Task { priority: Priority::High, ..base.clone() };
+        let theirs: Task = Task { status: Status::InProgress, tags: vec!["docs".to_string()], ..base.clone() };
+
+        let (merged, conflict) = merge_task(&base, &mine, &theirs);
+        assert!(conflict.is_none());
+        assert_eq!(merged.priority, Priority::High);
+        assert_eq!(merged.status, Status::InProgress);
+        assert_eq!(merged.tags, ["docs"]);
+    }
+
+    #[test]
+    fn merge_task_keeps_mine_and_reports_fields_changed_on_both_sides()
+    {
+        let base: Task = task(1, "Write docs");
+        let mine: Task = Task { task: "Write the docs".to_string(), project: Some("web".to_string()), ..base.clone() };
+        let theirs: Task = Task { task: "Write more docs".to_string(), priority: Priority::Low, ..base.clone() };
+
+        let (merged, conflict) = merge_task(&base, &mine, &theirs);
+        assert_eq!(merged.task, "Write the docs");
+        assert_eq!(merged.priority, Priority::Low);
+        let conflict: TaskConflict = conflict.unwrap();
+        assert_eq!(conflict.id, 1);
+        assert_eq!(conflict.fields, ["task"]);
+        assert_eq!(conflict.theirs.task, "Write more docs");
+        assert_eq!(conflict.theirs.project.as_deref(), Some("web"));
+    }
+
+    #[test]
+    fn same_change_on_both_sides_is_no_conflict()
+    {
+        let base: Task = task(1, "Write docs");
+        let changed: Task = Task { status: Status::Done, ..base.clone() };
+        let (merged, conflict) = merge_task(&base, &changed, &changed);
+        assert!(conflict.is_none());
+        assert_eq!(merged.status, Status::Done);
+    }
+
+    #[test]
+    fn merge_tasks_handles_adds_and_deletes()
+    {
+        let base: Vec<Task> = vec![task(1, "Kept"), task(2, "Deleted by me"), task(3, "Deleted by them"), task(4, "Edited and deleted")];
+        let mine: Vec<Task> = vec![task(3, "Deleted by them"), task(1, "Kept"), Task { priority: Priority::High, ..task(4, "Edited and deleted") }, task(5, "Mine")];
+        let theirs: Vec<Task> = vec![task(1, "Kept"), task(2, "Deleted by me"), task(5, "Theirs"), task(6, "Also theirs")];
+
+        let (merged, conflicts) = merge_tasks_with_conflicts(&base, &mine, &theirs);
+        assert!(conflicts.is_empty());
+        // Mine decides the order, the task that clashes with mine on id waits for a new one
+        assert_eq!(titles(&merged), [(1, "Kept"), (4, "Edited and deleted"), (5, "Mine"), (6, "Also theirs"), (0, "Theirs")]);
+
+        let mut tasks: Tasks = Tasks { next_id: 7, tasks: merged, ..Tasks::default() };
+        tasks.assign_ids();
+        assert_eq!(tasks.tasks.last().map(|task| task.id), Some(7));
+    }
+
+    #[test]
+    fn merge_tasks_reports_every_conflicting_task()
+    {
+        let base: Vec<Task> = vec![task(1, "One"), task(2, "Two")];
+        let mine: Vec<Task> = vec![task(1, "One, mine"), task(2, "Two, mine")];
+        let theirs: Vec<Task> = vec![task(1, "One, theirs"), task(2, "Two")];
+
+        let (merged, conflicts) = merge_tasks_with_conflicts(&base, &mine, &theirs);
+        assert_eq!(titles(&merged), [(1, "One, mine"), (2, "Two, mine")]);
+        let ids: Vec<u64> = conflicts.iter().map(|conflict| conflict.id).collect();
+        assert_eq!(ids, [1]);
+    }
+
+    #[test]
+    fn merge_entries_keeps_both_additions_and_removals()
+    {
+        let merged: Vec<u32> = merge_entries(&[1, 2, 3], vec![1, 3, 4], vec![2, 3, 5], |entry| *entry);
+        assert_eq!(merged, [3, 5, 4]);
+        let merged: Vec<u32> = merge_entries(&[], vec![1, 2], vec![2, 3], |entry| *entry);
+        assert_eq!(merged, [2, 3, 1]);
+    }
+}
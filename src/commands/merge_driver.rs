@@ -0,0 +1,410 @@
+use std::{
+    env,
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    commands::{
+        archive::Archive,
+        git,
+        merge::{self, TaskConflict},
+        runner::RunHistory,
+        tasks::{Task, Tasks},
+    },
+    files::{self, directory::{self, Order}, migrations},
+};
+
+
+#[derive(Debug, clap::Subcommand)]
+pub enum MergeDriverCli
+{
+    /// Tell git to merge the files in .dashboard with this dashboard
+    Install,
+
+    /// Merge one file, git calls this as `merge-driver merge %O %A %B %P`
+    Merge
+    {
+        /// The common ancestor
+        base: PathBuf,
+        /// Our side, the result is written here
+        current: PathBuf,
+        /// Their side
+        other: PathBuf,
+        /// Where the file is in the repository
+        path: Option<PathBuf>,
+    },
+}
+
+// The name the driver is registered under in the git config and .gitattributes
+const DRIVER: &str = "dashboard";
+
+// Returns the exit code for git, anything but 0 means the merge has conflicts
+pub fn run(command: &MergeDriverCli) -> i32
+{
+    match command
+    {
+        MergeDriverCli::Install => install(),
+        MergeDriverCli::Merge { base, current, other, path } =>
+        {
+            merge_file(base, current, other, path.as_deref().unwrap_or(current))
+        }
+    }
+}
+
+fn install() -> i32
+{
+    let base_path: PathBuf = files::base_path();
+    if !base_path.exists()
+    {
+        eprintln!("There is no .dashboard here to merge");
+        return 1;
+    }
+    let Ok(program) = env::current_exe() else
+    {
+        eprintln!("Could not find where the dashboard is installed");
+        return 1;
+    };
+
+    // The config stays in this clone, every teammate runs install once
+    let repo: PathBuf = git::project_dir();
+    let driver: String = format!("{} merge-driver merge %O %A %B %P", shell_words::quote(&program.to_string_lossy()));
+    let name_key: String = format!("merge.{}.name", DRIVER);
+    let driver_key: String = format!("merge.{}.driver", DRIVER);
+    for (key, value) in [(name_key.as_str(), "project dashboard tasks"), (driver_key.as_str(), driver.as_str())]
+    {
+        if let Err(error) = git::git(&repo, &["config", key, value])
+        {
+            eprintln!("{}", error);
+            return 1;
+        }
+    }
+
+    // Committed with .dashboard, so git knows which files use the driver
+    let mut attributes_path: PathBuf = base_path;
+    attributes_path.push(".gitattributes");
+    let attributes: String = fs::read_to_string(&attributes_path).unwrap_or_default();
+    let line: String = format!("*.toml merge={}", DRIVER);
+    if !attributes.lines().any(|existing| existing.trim() == line)
+    {
+        let separator: &str = if attributes.is_empty() || attributes.ends_with('\n') { "" } else { "\n" };
+        if fs::write(&attributes_path, format!("{}{}{}\n", attributes, separator, line)).is_err()
+        {
+            eprintln!("Could not write {}", attributes_path.display());
+            return 1;
+        }
+    }
+    println!("git now merges the files in .dashboard by task, commit .dashboard/.gitattributes to share it");
+    0
+}
+
+// The kinds of files in .dashboard that can be merged by their content
+enum FileKind
+{
+    Tasks,
+    TaskFile,
+    Order,
+    Archive,
+    Runs,
+    Other,
+}
+
+fn file_kind(path: &Path) -> FileKind
+{
+    let in_tasks_dir: bool = path.parent().is_some_and(|parent| parent.ends_with("tasks"));
+    let stem: &str = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+    if in_tasks_dir
+    {
+        return if path.file_name().is_some_and(|name| name == directory::ORDER_FILE) { FileKind::Order } else { FileKind::TaskFile };
+    }
+    match stem
+    {
+        "tasks" => FileKind::Tasks,
+        "archive" => FileKind::Archive,
+        "runs" => FileKind::Runs,
+        _ => FileKind::Other,
+    }
+}
+
+fn merge_file(base: &Path, current: &Path, other: &Path, path: &Path) -> i32
+{
+    let read = |path: &Path| fs::read_to_string(path).unwrap_or_default();
+    let (base_content, mine_content, theirs_content) = (read(base), read(current), read(other));
+
+    let merged: Option<(String, usize)> = match file_kind(path)
+    {
+        FileKind::Tasks => merge_tasks_file(&base_content, &mine_content, &theirs_content),
+        FileKind::TaskFile => merge_task_file(&base_content, &mine_content, &theirs_content),
+        FileKind::Order => merge_order_file(&base_content, &mine_content, &theirs_content),
+        FileKind::Archive =>
+        {
+            merge_list::<Archive, _>("archive", &base_content, &mine_content, &theirs_content, |base, mine, theirs|
+            {
+                let mut entries = merge::merge_entries(&base.entries, mine.entries, theirs.entries, |entry| (entry.archived_at, entry.task.id));
+                entries.sort_by_key(|entry| entry.archived_at);
+                Archive { entries, ..Archive::default() }
+            })
+        }
+        FileKind::Runs =>
+        {
+            merge_list::<RunHistory, _>("runs", &base_content, &mine_content, &theirs_content, |base, mine, theirs|
+            {
+                let mut runs = merge::merge_entries(&base.runs, mine.runs, theirs.runs, |run| (run.started, run.command.clone()));
+                runs.sort_by_key(|run| run.started);
+                RunHistory { runs }
+            })
+        }
+        FileKind::Other => None,
+    };
+
+    // Files that can not be read, or that are not about tasks, get the normal line merge
+    let Some((content, conflicts)) = merged else
+    {
+        return merge_lines(base, current, other);
+    };
+    if fs::write(current, content).is_err()
+    {
+        eprintln!("Could not write the merge of {}", path.display());
+        return 1;
+    }
+    if conflicts > 0
+    {
+        eprintln!("{}: conflicting edits in {} task(s), pick a side between the markers", path.display(), conflicts);
+        return 1;
+    }
+    0
+}
+
+// What the file holds at the current version, None when it can not be read or is too new
+fn parse<T>(name: &str, content: &str) -> Option<T>
+where
+    T: DeserializeOwned,
+{
+    let (table, _) = migrations::upgrade(name, content).ok()?;
+    table.try_into().ok()
+}
+
+fn with_version(content: String) -> String
+{
+    format!("version = {}\n\n{}", migrations::VERSION, content)
+}
+
+fn merge_tasks_file(base: &str, mine: &str, theirs: &str) -> Option<(String, usize)>
+{
+    let base: Tasks = parse("tasks", base)?;
+    let mine: Tasks = parse("tasks", mine)?;
+    let theirs: Tasks = parse("tasks", theirs)?;
+
+    let (merged, conflicts) = merge::merge_tasks_with_conflicts(&base.tasks, &mine.tasks, &theirs.tasks);
+    let mut tasks: Tasks = Tasks { next_id: mine.next_id.max(theirs.next_id), tasks: merged, ..Tasks::default() };
+    tasks.assign_ids();
+
+    // Written a task at a time, so a conflict only surrounds the task it is about
+    #[derive(Serialize)]
+    struct Block<'a>
+    {
+        tasks: [&'a Task; 1],
+    }
+    let block = |task: &Task| toml::to_string_pretty(&Block { tasks: [task] }).unwrap_or_default();
+
+    let mut content: String = format!("next_id = {}\n", tasks.next_id);
+    for task in &tasks.tasks
+    {
+        content.push('\n');
+        match conflicts.iter().find(|conflict| conflict.id == task.id)
+        {
+            Some(conflict) => content.push_str(&conflict_block(conflict, &block(task), &block(&conflict.theirs))),
+            None => content.push_str(&block(task)),
+        }
+    }
+    Some((with_version(content), conflicts.len()))
+}
+
+fn merge_task_file(base: &str, mine: &str, theirs: &str) -> Option<(String, usize)>
+{
+    let mine_task: Task = parse("task", mine)?;
+    let theirs_task: Task = parse("task", theirs)?;
+    let mine_content: String = toml::to_string_pretty(&mine_task).ok()?;
+
+    // Files are named by UUID, so this is the same task added on both sides, like a task from
+    // before UUIDs that both sides gave one. Only a person can tell which version to keep
+    let Some(base_task) = parse::<Task>("task", base) else
+    {
+        let theirs_content: String = toml::to_string_pretty(&theirs_task).ok()?;
+        if mine_content == theirs_content
+        {
+            return Some((with_version(mine_content), 0));
+        }
+        let conflict: TaskConflict = TaskConflict { id: mine_task.id, fields: vec!["the whole task".to_string()], theirs: theirs_task };
+        return Some((with_version(conflict_block(&conflict, &mine_content, &theirs_content)), 1));
+    };
+
+    let (merged, conflict) = merge::merge_task(&base_task, &mine_task, &theirs_task);
+    let merged_content: String = toml::to_string_pretty(&merged).ok()?;
+    match conflict
+    {
+        Some(conflict) =>
+        {
+            let theirs_content: String = toml::to_string_pretty(&conflict.theirs).ok()?;
+            Some((with_version(conflict_block(&conflict, &merged_content, &theirs_content)), 1))
+        }
+        None => Some((with_version(merged_content), 0)),
+    }
+}
+
+// Mine keeps its order without the tasks they removed, and the tasks they added go at the end
+fn merge_order_file(base: &str, mine: &str, theirs: &str) -> Option<(String, usize)>
+{
+    let base: Order = parse("order", base)?;
+    let mine: Order = parse("order", mine)?;
+    let theirs: Order = parse("order", theirs)?;
+
+    let order: Vec<u64> = merge::merge_entries(&base.order, theirs.order, mine.order, |id| *id);
+    let merged: Order = Order { next_id: mine.next_id.max(theirs.next_id), order };
+    Some((with_version(toml::to_string_pretty(&merged).ok()?), 0))
+}
+
+fn merge_list<T, F>(name: &str, base: &str, mine: &str, theirs: &str, merge: F) -> Option<(String, usize)>
+where
+    T: DeserializeOwned + Serialize,
+    F: FnOnce(T, T, T) -> T,
+{
+    let merged: T = merge(parse(name, base)?, parse(name, mine)?, parse(name, theirs)?);
+    Some((with_version(toml::to_string_pretty(&merged).ok()?), 0))
+}
+
+// Both versions of the task between git's conflict markers, only the conflicting fields differ
+fn conflict_block(conflict: &TaskConflict, mine: &str, theirs: &str) -> String
+{
+    format!(
+        "<<<<<<< ours, task #{} changed {} on both sides\n{}=======\n{}>>>>>>> theirs\n",
+        conflict.id, conflict.fields.join(", "), mine, theirs
+    )
+}
+
+// The merge git would do without the driver, with conflict markers in the current file
+fn merge_lines(base: &Path, current: &Path, other: &Path) -> i32
+{
+    let status = process::Command::new("git")
+        .args(["merge-file", "-L", "ours", "-L", "base", "-L", "theirs"])
+        .args([current, base, other])
+        .status();
+    match status
+    {
+        Ok(status) if status.success() => 0,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::structs::Priority;
+
+    fn task(id: u64, title: &str) -> Task
+    {
+        Task { id, ..Task::new(title.to_string(), Priority::Medium, String::new()) }
+    }
+
+    fn tasks_file(tasks: &[Task]) -> String
+    {
+        let next_id: u64 = tasks.iter().map(|task| task.id + 1).max().unwrap_or(1);
+        toml::to_string_pretty(&Tasks { next_id, tasks: tasks.to_vec(), ..Tasks::default() }).unwrap()
+    }
+
+    fn task_file(task: &Task) -> String
+    {
+        with_version(toml::to_string_pretty(task).unwrap())
+    }
+
+    #[test]
+    fn tasks_file_merges_fields_and_renumbers_added_tasks()
+    {
+        let base: Vec<Task> = vec![task(1, "Write docs"), task(2, "Fix bug")];
+        let mut mine: Vec<Task> = base.clone();
+        mine[0].priority = Priority::High;
+        mine.push(task(3, "Mine"));
+        let mut theirs: Vec<Task> = base.clone();
+        theirs[1].project = Some("api".to_string());
+        theirs.push(task(3, "Theirs"));
+
+        let (content, conflicts) = merge_tasks_file(&tasks_file(&base), &tasks_file(&mine), &tasks_file(&theirs)).unwrap();
+        assert_eq!(conflicts, 0);
+        let merged: Tasks = parse("tasks", &content).unwrap();
+        let titles: Vec<(u64, &str)> = merged.tasks.iter().map(|task| (task.id, task.task.as_str())).collect();
+        assert_eq!(titles, [(1, "Write docs"), (2, "Fix bug"), (3, "Mine"), (4, "Theirs")]);
+        assert_eq!(merged.tasks[0].priority, Priority::High);
+        assert_eq!(merged.tasks[1].project.as_deref(), Some("api"));
+        assert_eq!(merged.next_id, 5);
+    }
+
+    #[test]
+    fn tasks_file_marks_only_the_conflicting_task()
+    {
+        let base: Vec<Task> = vec![task(1, "Write docs"), task(2, "Fix bug")];
+        let mut mine: Vec<Task> = base.clone();
+        mine[1].task = "Fix the bug".to_string();
+        let mut theirs: Vec<Task> = base.clone();
+        theirs[1].task = "Fix that bug".to_string();
+
+        let (content, conflicts) = merge_tasks_file(&tasks_file(&base), &tasks_file(&mine), &tasks_file(&theirs)).unwrap();
+        assert_eq!(conflicts, 1);
+        assert_eq!(content.matches("<<<<<<<").count(), 1);
+        let (before, block) = content.split_once("<<<<<<<").unwrap();
+        assert!(before.contains("Write docs"));
+        assert!(block.starts_with(" ours, task #2 changed task on both sides"));
+        assert!(block.contains("Fix the bug") && block.contains("Fix that bug"));
+    }
+
+    #[test]
+    fn task_file_merges_fields_and_keeps_the_version()
+    {
+        let base: Task = task(1, "Write docs");
+        let mine: Task = Task { priority: Priority::High, ..base.clone() };
+        let theirs: Task = Task { project: Some("web".to_string()), ..base.clone() };
+
+        let (content, conflicts) = merge_task_file(&task_file(&base), &task_file(&mine), &task_file(&theirs)).unwrap();
+        assert_eq!(conflicts, 0);
+        assert!(content.starts_with(&format!("version = {}\n", migrations::VERSION)));
+        let merged: Task = parse("task", &content).unwrap();
+        assert_eq!(merged.priority, Priority::High);
+        assert_eq!(merged.project.as_deref(), Some("web"));
+    }
+
+    #[test]
+    fn task_file_conflict_keeps_the_version_outside_the_markers()
+    {
+        let base: Task = task(1, "Write docs");
+        let mine: Task = Task { priority: Priority::High, ..base.clone() };
+        let theirs: Task = Task { priority: Priority::Low, ..base.clone() };
+
+        let (content, conflicts) = merge_task_file(&task_file(&base), &task_file(&mine), &task_file(&theirs)).unwrap();
+        assert_eq!(conflicts, 1);
+        let (before, block) = content.split_once("<<<<<<<").unwrap();
+        assert!(before.starts_with("version = "));
+        assert!(!block.contains("version = "));
+        assert!(block.contains("priority = \"High\"") && block.contains("priority = \"Low\""));
+    }
+
+    #[test]
+    fn order_file_keeps_mine_and_adds_theirs_at_the_end()
+    {
+        let order = |next_id: u64, order: &[u64]| with_version(toml::to_string_pretty(&Order { next_id, order: order.to_vec() }).unwrap());
+        let (content, conflicts) = merge_order_file(&order(4, &[1, 2, 3]), &order(5, &[3, 1, 2, 4]), &order(6, &[1, 3, 5])).unwrap();
+        assert_eq!(conflicts, 0);
+        let merged: Order = parse("order", &content).unwrap();
+        assert_eq!(merged.order, [3, 1, 4, 5]);
+        assert_eq!(merged.next_id, 6);
+    }
+
+    #[test]
+    fn unreadable_files_fall_back_to_the_line_merge()
+    {
+        assert!(merge_tasks_file("", "<<<<<<< not toml", "").is_none());
+        assert!(merge_task_file("", &task_file(&task(1, "Task")), "version = 99\n").is_none());
+    }
+}
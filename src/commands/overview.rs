@@ -0,0 +1,10 @@
+// What the project is about, shown on the overview screen
+#[derive(Debug, serde::Deserialize, serde::Serialize, Default)]
+pub struct Overview
+{
+    #[serde(default)]
+    pub name: Option<String>,
+
+    #[serde(default)]
+    pub description: String,
+}
@@ -0,0 +1,230 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+
+
+// How a task comes back after it is done, written as text like "weekly mon fri" or "every 3 days"
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub enum Recurrence
+{
+    Daily,
+    Weekly(Vec<Weekday>),
+    Monthly,
+    // Counted from the day the task was done instead of the due date
+    AfterCompletion(u32),
+}
+
+impl Recurrence
+{
+    // The due date of the next instance, always after today. None when it would be past the last date there is.
+    // `recurs_from` is the date a monthly task was first due on, so the 31st stays the 31st after a short month
+    pub fn next_due(&self, due: Option<NaiveDate>, recurs_from: Option<NaiveDate>, today: NaiveDate) -> Option<NaiveDate>
+    {
+        let start: NaiveDate = due.unwrap_or(today);
+        match self
+        {
+            Recurrence::AfterCompletion(days) => today.checked_add_days(Days::new(*days as u64)),
+            Recurrence::Monthly =>
+            {
+                let anchor: NaiveDate = self.anchor(due, recurs_from, today)?;
+                (1..)
+                    .map(|months| anchor.checked_add_months(Months::new(months)))
+                    .find(|next| next.is_none_or(|next| next > start && next > today))
+                    .flatten()
+            }
+            _ =>
+            {
+                let mut next: NaiveDate = self.step(start)?;
+                while next <= today
+                {
+                    next = self.step(next)?;
+                }
+                Some(next)
+            }
+        }
+    }
+
+    // Where the months of a monthly task are counted from. A due date that was moved by hand
+    // to another day starts the count again
+    pub fn anchor(&self, due: Option<NaiveDate>, recurs_from: Option<NaiveDate>, today: NaiveDate) -> Option<NaiveDate>
+    {
+        if *self != Recurrence::Monthly
+        {
+            return None;
+        }
+        let start: NaiveDate = due.unwrap_or(today);
+        let last_day_of_month: bool = start.succ_opt().is_none_or(|next| next.month() != start.month());
+        let anchor: Option<NaiveDate> = recurs_from
+            .filter(|anchor| *anchor <= start && last_day_of_month && anchor.day() > start.day());
+        Some(anchor.unwrap_or(start))
+    }
+
+    fn step(&self, date: NaiveDate) -> Option<NaiveDate>
+    {
+        match self
+        {
+            Recurrence::Daily => date.checked_add_days(Days::new(1)),
+            // Without days it comes back on the same day of the week
+            Recurrence::Weekly(days) if days.is_empty() => date.checked_add_days(Days::new(7)),
+            Recurrence::Weekly(days) =>
+            {
+                (1..=7)
+                    .filter_map(|offset| date.checked_add_days(Days::new(offset)))
+                    .find(|next| days.contains(&next.weekday()))
+            }
+            Recurrence::Monthly => date.checked_add_months(Months::new(1)),
+            Recurrence::AfterCompletion(days) => date.checked_add_days(Days::new(*days as u64)),
+        }
+    }
+}
+
+impl fmt::Display for Recurrence
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekly(days) if days.is_empty() => write!(f, "weekly"),
+            Recurrence::Weekly(days) =>
+            {
+                let days: Vec<String> = days.iter().map(|day| day.to_string().to_lowercase()).collect();
+                write!(f, "weekly {}", days.join(" "))
+            }
+            Recurrence::Monthly => write!(f, "monthly"),
+            Recurrence::AfterCompletion(1) => write!(f, "every day"),
+            Recurrence::AfterCompletion(days) => write!(f, "every {} days", days),
+        }
+    }
+}
+
+impl FromStr for Recurrence
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let lowercase: String = s.to_lowercase();
+        let words: Vec<&str> = lowercase.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        match words.as_slice()
+        {
+            ["daily"] => Ok(Recurrence::Daily),
+            ["monthly"] => Ok(Recurrence::Monthly),
+            ["weekly", days @ ..] =>
+            {
+                let mut weekdays: Vec<Weekday> = days.iter()
+                    .map(|day| day.parse::<Weekday>().map_err(|_| format!("'{}' is not a day of the week", day)))
+                    .collect::<Result<_, _>>()?;
+                weekdays.sort_by_key(|day| day.num_days_from_monday());
+                weekdays.dedup();
+                Ok(Recurrence::Weekly(weekdays))
+            }
+            ["every", "day"] => Ok(Recurrence::AfterCompletion(1)),
+            ["every", days, "days"] | ["every", days] =>
+            {
+                match days.parse::<u32>()
+                {
+                    Ok(days) if days > 0 => Ok(Recurrence::AfterCompletion(days)),
+                    _ => Err(format!("'{}' is not a positive number of days", days)),
+                }
+            }
+            _ => Err(format!("'{}' is not a recurrence, use daily, weekly <days>, monthly or every <n> days", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for Recurrence
+{
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error>
+    {
+        value.parse()
+    }
+}
+
+impl From<Recurrence> for String
+{
+    fn from(value: Recurrence) -> Self
+    {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate
+    {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn parses_and_prints_rules()
+    {
+        for rule in ["daily", "weekly", "weekly mon thu", "monthly", "every day", "every 3 days"]
+        {
+            assert_eq!(rule.parse::<Recurrence>().unwrap().to_string(), rule);
+        }
+        assert_eq!("Weekly Thu,Mon,mon".parse::<Recurrence>(), Ok(Recurrence::Weekly(vec![Weekday::Mon, Weekday::Thu])));
+        assert!("weekly someday".parse::<Recurrence>().is_err());
+        assert!("every 0 days".parse::<Recurrence>().is_err());
+        assert!("yearly".parse::<Recurrence>().is_err());
+    }
+
+    #[test]
+    fn weekly_without_days_is_a_week_later()
+    {
+        let weekly: Recurrence = "weekly".parse().unwrap();
+        // 2025-01-06 is a Monday
+        assert_eq!(weekly.step(date(2025, 1, 6)), Some(date(2025, 1, 13)));
+        assert_eq!(weekly.next_due(Some(date(2025, 1, 6)), None, date(2025, 1, 6)), Some(date(2025, 1, 13)));
+    }
+
+    #[test]
+    fn weekly_with_days_goes_to_the_next_listed_day()
+    {
+        let weekly: Recurrence = "weekly mon,thu".parse().unwrap();
+        assert_eq!(weekly.step(date(2025, 1, 6)), Some(date(2025, 1, 9)));
+        assert_eq!(weekly.step(date(2025, 1, 9)), Some(date(2025, 1, 13)));
+        assert_eq!(weekly.step(date(2025, 1, 10)), Some(date(2025, 1, 13)));
+    }
+
+    #[test]
+    fn next_due_is_after_today()
+    {
+        let daily: Recurrence = Recurrence::Daily;
+        assert_eq!(daily.next_due(Some(date(2025, 1, 1)), None, date(2025, 1, 10)), Some(date(2025, 1, 11)));
+        assert_eq!(Recurrence::Monthly.next_due(Some(date(2025, 1, 31)), None, date(2025, 1, 31)), Some(date(2025, 2, 28)));
+        assert_eq!(Recurrence::AfterCompletion(3).next_due(Some(date(2020, 1, 1)), None, date(2025, 1, 10)), Some(date(2025, 1, 13)));
+    }
+
+    #[test]
+    fn monthly_keeps_the_day_it_started_on()
+    {
+        let monthly: Recurrence = Recurrence::Monthly;
+        let anchor: Option<NaiveDate> = Some(date(2025, 1, 31));
+        assert_eq!(monthly.next_due(Some(date(2025, 2, 28)), anchor, date(2025, 2, 28)), Some(date(2025, 3, 31)));
+        // Catching up from long ago does not drift either
+        assert_eq!(monthly.next_due(Some(date(2025, 1, 31)), None, date(2025, 4, 2)), Some(date(2025, 4, 30)));
+        assert_eq!(monthly.next_due(Some(date(2025, 1, 31)), None, date(2025, 4, 30)), Some(date(2025, 5, 31)));
+        // A due date moved by hand counts from its own day
+        assert_eq!(monthly.next_due(Some(date(2025, 2, 15)), anchor, date(2025, 2, 15)), Some(date(2025, 3, 15)));
+        assert_eq!(monthly.anchor(Some(date(2025, 2, 28)), anchor, date(2025, 2, 28)), anchor);
+        assert_eq!(Recurrence::Daily.anchor(Some(date(2025, 2, 28)), anchor, date(2025, 2, 28)), None);
+    }
+
+    #[test]
+    fn stops_at_the_last_date()
+    {
+        assert_eq!(Recurrence::Monthly.next_due(Some(NaiveDate::MAX), None, NaiveDate::MAX), None);
+        assert_eq!(Recurrence::Daily.next_due(None, None, NaiveDate::MAX), None);
+        assert_eq!("weekly mon".parse::<Recurrence>().unwrap().next_due(None, None, NaiveDate::MAX), None);
+    }
+}
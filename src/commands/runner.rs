@@ -0,0 +1,381 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::{BufRead, BufReader, Read},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread,
+};
+
+use chrono::{DateTime, Local, TimeDelta};
+
+use crate::{commands::{git, merge}, files};
+
+
+// How many runs are kept in runs.toml, the oldest go first
+const RUN_LIMIT: usize = 1000;
+// Only the end of the output is saved
+const SAVED_OUTPUT_LINES: usize = 100;
+// And only the end is kept while running, a command that never stops printing does not fill the memory
+const OUTPUT_LIMIT: usize = 10_000;
+// How long output that was already on its way is still taken in after the command exited
+const OUTPUT_GRACE: TimeDelta = TimeDelta::milliseconds(200);
+
+
+// The commands from .dashboard/commands.toml, by name
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct ProjectCommands
+{
+    #[serde(flatten)]
+    pub commands: BTreeMap<String, CommandDefinition>,
+}
+
+// Either `test = "cargo test --all"`, or a table when it needs a directory or environment
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum CommandDefinition
+{
+    Simple(String),
+    Full
+    {
+        run: String,
+        // Relative to the project
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        dir: Option<PathBuf>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        env: BTreeMap<String, String>,
+    },
+}
+
+impl CommandDefinition
+{
+    pub fn command_line(&self) -> &str
+    {
+        match self
+        {
+            CommandDefinition::Simple(run) => run,
+            CommandDefinition::Full { run, .. } => run,
+        }
+    }
+}
+
+// Finished runs, from .dashboard/runs.toml
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct RunHistory
+{
+    #[serde(default)]
+    pub runs: Vec<RunRecord>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct RunRecord
+{
+    pub command: String,
+    pub command_line: String,
+    pub started: DateTime<Local>,
+    // In seconds
+    pub duration: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub output: String,
+}
+
+impl RunRecord
+{
+    pub fn succeeded(&self) -> bool
+    {
+        self.exit_code == Some(0)
+    }
+}
+
+impl RunHistory
+{
+    pub fn add(&mut self, record: RunRecord)
+    {
+        self.runs.push(record);
+        if self.runs.len() > RUN_LIMIT
+        {
+            self.runs.drain(..self.runs.len() - RUN_LIMIT);
+        }
+    }
+
+    // The runs of one command, oldest first
+    pub fn of_command<'a>(&'a self, command: &'a str) -> impl Iterator<Item = &'a RunRecord>
+    {
+        self.runs.iter().filter(move |record| record.command == command)
+    }
+
+    // Every command that has runs, in the order they were first run
+    pub fn commands(&self) -> Vec<&str>
+    {
+        let mut commands: Vec<&str> = Vec::new();
+        for record in &self.runs
+        {
+            if !commands.contains(&record.command.as_str())
+            {
+                commands.push(&record.command);
+            }
+        }
+        commands
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputLine
+{
+    pub text: String,
+    pub stderr: bool,
+}
+
+// A command that was started from the dashboard, running or finished
+#[derive(Debug)]
+pub struct Run
+{
+    pub name: String,
+    pub command_line: String,
+    pub started: DateTime<Local>,
+    pub finished: Option<DateTime<Local>>,
+    // None while running, and when killed by a signal
+    pub exit_code: Option<i32>,
+    pub output: VecDeque<OutputLine>,
+    child: Option<Child>,
+    receiver: Option<Receiver<OutputLine>>,
+    // When the command exited and with what code, the run finishes once its output is in
+    exited: Option<(DateTime<Local>, Option<i32>)>,
+}
+
+impl Run
+{
+    // Starts the command with its output piped, a thread per pipe sends the lines back
+    pub fn start(name: &str, definition: &CommandDefinition) -> Result<Self, String>
+    {
+        let words: Vec<String> = shell_words::split(definition.command_line())
+            .map_err(|error| format!("Could not parse \"{}\": {}", definition.command_line(), error))?;
+        let Some((program, args)) = words.split_first() else
+        {
+            return Err(format!("The command \"{}\" is empty", name));
+        };
+
+        let mut command: Command = Command::new(program);
+        command.args(args)
+            .current_dir(git::project_dir())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let CommandDefinition::Full { dir, env, .. } = definition
+        {
+            if let Some(dir) = dir
+            {
+                command.current_dir(git::project_dir().join(dir));
+            }
+            command.envs(env);
+        }
+
+        let mut child: Child = command.spawn().map_err(|error| format!("Could not start \"{}\": {}", program, error))?;
+        let (sender, receiver) = mpsc::channel();
+        if let Some(stdout) = child.stdout.take()
+        {
+            forward_lines(stdout, sender.clone(), false);
+        }
+        if let Some(stderr) = child.stderr.take()
+        {
+            forward_lines(stderr, sender, true);
+        }
+
+        Ok(Self
+        {
+            name: name.to_string(),
+            command_line: definition.command_line().to_string(),
+            started: Local::now(),
+            finished: None,
+            exit_code: None,
+            output: VecDeque::new(),
+            child: Some(child),
+            receiver: Some(receiver),
+            exited: None,
+        })
+    }
+
+    pub fn is_running(&self) -> bool
+    {
+        self.finished.is_none()
+    }
+
+    // Takes in the new output, and returns true once the command has finished.
+    // That is when it exited, a process it started can keep the pipes open for much longer
+    pub fn poll(&mut self) -> bool
+    {
+        if let Some(receiver) = self.receiver.as_ref()
+        {
+            loop
+            {
+                match receiver.try_recv()
+                {
+                    Ok(line) =>
+                    {
+                        if self.output.len() == OUTPUT_LIMIT
+                        {
+                            self.output.pop_front();
+                        }
+                        self.output.push_back(line);
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    // Both pipes are closed, so all output is in
+                    Err(TryRecvError::Disconnected) =>
+                    {
+                        self.receiver = None;
+                        break;
+                    }
+                }
+            }
+        }
+
+        if self.exited.is_none()
+            && let Some(child) = self.child.as_mut()
+            && let Ok(Some(status)) = child.try_wait()
+        {
+            self.exited = Some((Local::now(), status.code()));
+            self.child = None;
+        }
+        match self.exited
+        {
+            Some((exited, exit_code)) if self.receiver.is_none() || Local::now() - exited >= OUTPUT_GRACE =>
+            {
+                // The threads reading the pipes stop once they have nowhere to send to
+                self.receiver = None;
+                self.exit_code = exit_code;
+                self.finished = Some(exited);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // A finished run from the history, its output no longer knows what came from stderr
+    pub fn from_record(record: &RunRecord) -> Self
+    {
+        Self
+        {
+            name: record.command.clone(),
+            command_line: record.command_line.clone(),
+            started: record.started,
+            finished: Some(record.started + TimeDelta::milliseconds((record.duration * 1000.0) as i64)),
+            exit_code: record.exit_code,
+            output: record.output.lines().map(|line| OutputLine { text: line.to_string(), stderr: false }).collect(),
+            child: None,
+            receiver: None,
+            exited: None,
+        }
+    }
+
+    pub fn to_record(&self) -> RunRecord
+    {
+        let skipped: usize = self.output.len().saturating_sub(SAVED_OUTPUT_LINES);
+        RunRecord
+        {
+            command: self.name.clone(),
+            command_line: self.command_line.clone(),
+            started: self.started,
+            duration: self.duration().as_seconds_f64(),
+            exit_code: self.exit_code,
+            output: self.output.iter().skip(skipped).map(|line| line.text.as_str()).collect::<Vec<_>>().join("\n"),
+        }
+    }
+
+    pub fn kill(&mut self)
+    {
+        if let Some(child) = self.child.as_mut()
+        {
+            let _ = child.kill();
+        }
+    }
+
+    pub fn duration(&self) -> TimeDelta
+    {
+        self.finished.unwrap_or(Local::now()) - self.started
+    }
+}
+
+// Commands still running when the dashboard closes are stopped with it
+impl Drop for Run
+{
+    fn drop(&mut self)
+    {
+        if let Some(child) = self.child.as_mut()
+        {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+fn forward_lines<R>(pipe: R, sender: Sender<OutputLine>, stderr: bool)
+where
+    R: Read + Send + 'static,
+{
+    thread::spawn(move ||
+    {
+        // Output that is not utf-8 is shown lossy instead of stopping the reading
+        let mut reader = BufReader::new(pipe);
+        let mut buffer: Vec<u8> = Vec::new();
+        while reader.read_until(b'\n', &mut buffer).is_ok_and(|read| read > 0)
+        {
+            let text: String = String::from_utf8_lossy(&buffer).trim_end_matches(['\n', '\r']).to_string();
+            buffer.clear();
+            if sender.send(OutputLine { text, stderr }).is_err()
+            {
+                return;
+            }
+        }
+    });
+}
+
+pub fn write_runs(history: &mut RunHistory)
+{
+    if files::shared()
+    {
+        merge::merge_runs_with_disk(history);
+    }
+
+    let toml_str = toml::to_string_pretty(&history)
+        .expect("Failed to serialize runs");
+
+    files::write_file("runs", toml_str);
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn run(command_line: &str) -> Run
+    {
+        let mut run: Run = Run::start("test", &CommandDefinition::Simple(command_line.to_string())).unwrap();
+        let started: std::time::Instant = std::time::Instant::now();
+        while !run.poll()
+        {
+            assert!(started.elapsed().as_secs() < 5, "{} did not finish", command_line);
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+        run
+    }
+
+    #[test]
+    fn finishes_when_the_command_exits_even_if_a_child_holds_the_pipes()
+    {
+        let run: Run = run("sh -c 'sleep 10 & echo started; exit 3'");
+        assert_eq!(run.exit_code, Some(3));
+        assert_eq!(run.output.front().map(|line| line.text.as_str()), Some("started"));
+    }
+
+    #[test]
+    fn keeps_only_the_end_of_the_output()
+    {
+        let run: Run = run(&format!("seq {}", OUTPUT_LIMIT + 5));
+        assert_eq!(run.output.len(), OUTPUT_LIMIT);
+        assert_eq!(run.output.front().map(|line| line.text.as_str()), Some("6"));
+        assert_eq!(run.to_record().output.lines().count(), SAVED_OUTPUT_LINES);
+        assert_eq!(run.exit_code, Some(0));
+    }
+}
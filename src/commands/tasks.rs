@@ -1,28 +1,104 @@
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Local, NaiveDate, TimeDelta};
 use ratatui::widgets::ListState;
 use clap;
-use toml;
 
 use crate::{
-    files,
-    structs::Priority,
+    commands::{
+        archive::ArchiveKey,
+        estimates::Estimate,
+        git::{BranchLink, CommitLink},
+        merge,
+        recurrence::Recurrence,
+        time_tracking::{self, LogGrouping, TimeEntry},
+    },
+    files::{self, storage::{self, TaskQuery}},
+    structs::{Data, Priority, Status},
 };
 
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, clap::Subcommand)]
+pub enum TasksCli
+{
+    /// Show the tracked time, including archived tasks
+    Log
+    {
+        #[arg(long, value_enum, default_value = "day")]
+        by: LogGrouping,
+    },
+
+    /// List the tasks, optionally only the ones that match
+    List
+    {
+        #[command(flatten)]
+        query: TaskQuery,
+    },
+
+    /// Change a task by its id
+    Set
+    {
+        id: u64,
+
+        #[arg(long, value_enum)]
+        priority: Option<Priority>,
+
+        #[arg(long)]
+        project: Option<String>,
+
+        #[arg(long)]
+        due: Option<NaiveDate>,
+    },
+}
+
+// How many steps can be undone
+const UNDO_LIMIT: usize = 100;
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Default)]
 pub struct Tasks
 {
+    // The id the next new task gets, ids are never reused
+    #[serde(default)]
+    pub next_id: u64,
+
+    #[serde(default)]
     pub tasks: Vec<Task>,   
 
     #[serde(skip)]
     pub list_state: ListState,
+
+    // Indices of the tasks that are marked for a bulk action
+    #[serde(skip)]
+    pub marked: BTreeSet<usize>,
+
+    // Start of a range that is being marked, the end is the selected task
+    #[serde(skip)]
+    pub mark_anchor: Option<usize>,
+
+    #[serde(skip)]
+    pub undo_stack: Vec<UndoStep>,
+}
+
+// The tasks before a change, and how many tasks that change moved into the archive
+#[derive(Debug)]
+pub struct UndoStep
+{
+    pub tasks: Vec<Task>,
+    pub archived: Vec<ArchiveKey>,
 }
 
 fn priority_default() -> Priority { Priority::Low }
 fn description_default() -> String { String::new() }
+fn status_default() -> Status { Status::Todo }
 
 #[derive(Debug, serde::Deserialize, serde::Serialize, clap::Parser, Clone)]
 pub struct Task
 {
+    // 0 means no id has been given out yet
+    #[serde(default)]
+    #[arg(skip)]
+    pub id: u64,
+
     pub task: String,
 
     #[serde(default = "priority_default")]
@@ -30,15 +106,586 @@ pub struct Task
 
     #[serde(default = "description_default")]
     pub description: String,
+
+    #[serde(default = "status_default")]
+    #[arg(long, default_value = "todo")]
+    pub status: Status,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[arg(long)]
+    pub tags: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub project: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub due: Option<NaiveDate>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub recurrence: Option<Recurrence>,
+
+    // The due date a monthly task counts its months from, set on the instances after the first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub recurs_from: Option<NaiveDate>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub estimate: Option<Estimate>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub created: Option<DateTime<Local>>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[arg(skip)]
+    pub time_entries: Vec<TimeEntry>,
+
+    #[serde(default, skip_serializing_if = "is_zero")]
+    #[arg(skip)]
+    pub pomodoros: u32,
+
+    // Full hashes of the commits that mention this task
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[arg(skip)]
+    pub commits: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[arg(long = "branch")]
+    pub branches: Vec<String>,
+
+    // Where a task made from a code comment came from, like "src/main.rs:42"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub source: Option<String>,
+
+    // Stays the same when the id changes. Names the task's file in the directory layout,
+    // and Taskwarrior gets it too, so syncing again updates the task instead of adding it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub uuid: Option<String>,
+}
+
+fn is_zero(value: &u32) -> bool { *value == 0 }
+
+pub fn new_uuid() -> String
+{
+    uuid::Uuid::new_v4().to_string()
+}
+
+impl Task
+{
+    pub fn new(task: String, priority: Priority, description: String) -> Self
+    {
+        Self
+        {
+            id: 0,
+            task,
+            priority,
+            description,
+            status: status_default(),
+            tags: Vec::new(),
+            project: None,
+            due: None,
+            recurrence: None,
+            recurs_from: None,
+            estimate: None,
+            created: None,
+            time_entries: Vec::new(),
+            pomodoros: 0,
+            commits: Vec::new(),
+            branches: Vec::new(),
+            source: None,
+            uuid: None,
+        }
+    }
+
+    pub fn timer_running(&self) -> bool
+    {
+        self.time_entries.last().is_some_and(|entry| entry.end.is_none())
+    }
+
+    pub fn start_timer(&mut self, now: DateTime<Local>)
+    {
+        if !self.timer_running()
+        {
+            self.time_entries.push(TimeEntry { start: now, end: None });
+        }
+    }
+
+    pub fn stop_timer(&mut self, now: DateTime<Local>)
+    {
+        if let Some(entry) = self.time_entries.last_mut().filter(|entry| entry.end.is_none())
+        {
+            entry.end = Some(now);
+        }
+    }
+
+    pub fn tracked_time(&self, now: DateTime<Local>) -> TimeDelta
+    {
+        self.time_entries.iter().fold(TimeDelta::zero(), |total, entry| total + entry.duration(now))
+    }
+
+    // The next instance of a recurring task, as a fresh todo with the next due date
+    pub fn next_occurrence(&self, today: NaiveDate) -> Option<Task>
+    {
+        let recurrence: &Recurrence = self.recurrence.as_ref()?;
+        let mut next: Task = self.clone();
+        next.status = Status::Todo;
+        next.due = Some(recurrence.next_due(self.due, self.recurs_from, today)?);
+        next.recurs_from = recurrence.anchor(self.due, self.recurs_from, today);
+        next.time_entries.clear();
+        next.pomodoros = 0;
+        next.commits.clear();
+        next.branches.clear();
+        next.uuid = Some(new_uuid());
+        next.id = 0;
+        next.created = None;
+        Some(next)
+    }
+
+    pub fn is_finished(&self) -> bool
+    {
+        matches!(self.status, Status::Done | Status::Cancelled)
+    }
+
+    pub fn add_tag(&mut self, tag: &str)
+    {
+        if !self.tags.iter().any(|t| t == tag)
+        {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    pub fn remove_tag(&mut self, tag: &str)
+    {
+        self.tags.retain(|t| t != tag);
+    }
+}
+
+impl Tasks
+{
+    // Gives every task without an id one, for files from before tasks had ids
+    pub fn assign_ids(&mut self)
+    {
+        let highest: u64 = self.tasks.iter().map(|task| task.id).max().unwrap_or(0);
+        self.next_id = self.next_id.max(highest + 1);
+        for index in 0..self.tasks.len()
+        {
+            if self.tasks[index].id == 0
+            {
+                self.tasks[index].id = self.take_id();
+            }
+        }
+    }
+
+    // Links the commits and branches that mention a task id to that task. Returns whether anything
+    // was linked, and the task id and commit hash for every newly linked commit that fixes an unfinished task
+    pub fn link_git(&mut self, commits: &[CommitLink], branches: &[BranchLink]) -> (bool, Vec<(u64, String)>)
+    {
+        let mut linked: bool = false;
+        let mut fixes: Vec<(u64, String)> = Vec::new();
+        for task in self.tasks.iter_mut()
+        {
+            for commit in commits.iter().filter(|commit| commit.tasks.contains(&task.id))
+            {
+                if task.commits.contains(&commit.hash)
+                {
+                    continue;
+                }
+                task.commits.push(commit.hash.clone());
+                linked = true;
+                if commit.fixes.contains(&task.id) && !task.is_finished()
+                {
+                    fixes.push((task.id, commit.hash.clone()));
+                }
+            }
+            for branch in branches.iter().filter(|branch| branch.tasks.contains(&task.id))
+            {
+                if !task.branches.contains(&branch.name)
+                {
+                    task.branches.push(branch.name.clone());
+                    linked = true;
+                }
+            }
+        }
+        (linked, fixes)
+    }
+
+    fn take_id(&mut self) -> u64
+    {
+        self.next_id = self.next_id.max(1);
+        let id: u64 = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    // Adds a task at the end, restored tasks keep the id they had
+    pub fn add(&mut self, mut task: Task)
+    {
+        if task.id == 0 || self.find(task.id).is_some()
+        {
+            task.id = self.take_id();
+        }
+        self.next_id = self.next_id.max(task.id + 1);
+        task.created.get_or_insert_with(Local::now);
+        task.uuid.get_or_insert_with(new_uuid);
+        self.tasks.push(task);
+    }
+
+    pub fn find(&self, id: u64) -> Option<usize>
+    {
+        self.tasks.iter().position(|task| task.id == id)
+    }
+
+    // Save the current tasks so the next change can be undone as a whole
+    pub fn snapshot(&mut self)
+    {
+        if self.undo_stack.len() >= UNDO_LIMIT
+        {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(UndoStep { tasks: self.tasks.clone(), archived: Vec::new() });
+    }
+
+    // Remember that the last change archived tasks, so undo can take them out of the archive again
+    pub fn record_archived(&mut self, keys: Vec<ArchiveKey>)
+    {
+        if let Some(step) = self.undo_stack.last_mut()
+        {
+            step.archived.extend(keys);
+        }
+    }
+
+    // Returns the archive entries that have to be removed again
+    pub fn undo(&mut self) -> Option<Vec<ArchiveKey>>
+    {
+        let step: UndoStep = self.undo_stack.pop()?;
+        self.tasks = step.tasks;
+        self.clear_marks();
+        self.clamp_selection();
+        Some(step.archived)
+    }
+
+    pub fn toggle_mark(&mut self, index: usize)
+    {
+        if !self.marked.remove(&index)
+        {
+            self.marked.insert(index);
+        }
+    }
+
+    // Starts a range on the first call, marks everything up to the selected task on the second
+    pub fn toggle_range(&mut self)
+    {
+        let Some(selected) = self.list_state.selected() else
+        {
+            return;
+        };
+        match self.mark_anchor.take()
+        {
+            Some(anchor) => self.marked.extend(anchor.min(selected)..=anchor.max(selected)),
+            None => self.mark_anchor = Some(selected),
+        }
+    }
+
+    pub fn clear_marks(&mut self)
+    {
+        self.marked.clear();
+        self.mark_anchor = None;
+    }
+
+    pub fn has_marks(&self) -> bool
+    {
+        !self.marked.is_empty() || self.mark_anchor.is_some()
+    }
+
+    pub fn is_marked(&self, index: usize) -> bool
+    {
+        if self.marked.contains(&index)
+        {
+            return true;
+        }
+        match (self.mark_anchor, self.list_state.selected())
+        {
+            (Some(anchor), Some(selected)) => (anchor.min(selected)..=anchor.max(selected)).contains(&index),
+            _ => false,
+        }
+    }
+
+    // The tasks a bulk action works on, the marked ones or else the selected one
+    pub fn targets(&self) -> Vec<usize>
+    {
+        let targets: Vec<usize> = (0..self.tasks.len())
+            .filter(|index| self.is_marked(*index))
+            .collect();
+        if !targets.is_empty()
+        {
+            return targets;
+        }
+        self.list_state.selected()
+            .filter(|index| *index < self.tasks.len())
+            .into_iter()
+            .collect()
+    }
+
+    // Applies a change to all targets as one undoable step
+    pub fn bulk_update<F>(&mut self, update: F) -> usize
+    where
+        F: Fn(&mut Task),
+    {
+        let targets: Vec<usize> = self.targets();
+        if targets.is_empty()
+        {
+            return 0;
+        }
+        self.snapshot();
+        for index in &targets
+        {
+            update(&mut self.tasks[*index]);
+        }
+        self.clear_marks();
+        targets.len()
+    }
+
+    // Returns the removed tasks, in list order
+    pub fn bulk_delete(&mut self) -> Vec<Task>
+    {
+        let targets: Vec<usize> = self.targets();
+        if targets.is_empty()
+        {
+            return Vec::new();
+        }
+        self.snapshot();
+        // A timer left running in the archive would keep counting
+        let now: DateTime<Local> = Local::now();
+        let mut removed: Vec<Task> = targets.iter()
+            .rev()
+            .map(|index| self.tasks.remove(*index))
+            .collect();
+        for task in removed.iter_mut()
+        {
+            task.stop_timer(now);
+        }
+        removed.reverse();
+        self.clear_marks();
+        self.clamp_selection();
+        removed
+    }
+
+    // Takes the done and cancelled tasks out of the list, done recurring tasks leave their next instance in their place
+    pub fn take_finished(&mut self) -> Vec<Task>
+    {
+        let now: DateTime<Local> = Local::now();
+        let today: NaiveDate = now.date_naive();
+        let mut finished: Vec<Task> = Vec::new();
+        let mut active: Vec<Task> = Vec::new();
+        for mut task in std::mem::take(&mut self.tasks)
+        {
+            if !task.is_finished()
+            {
+                active.push(task);
+                continue;
+            }
+            task.stop_timer(now);
+            if let Some(mut next) = task.next_occurrence(today).filter(|_| task.status == Status::Done)
+            {
+                next.id = self.take_id();
+                next.created = Some(now);
+                active.push(next);
+            }
+            finished.push(task);
+        }
+        self.tasks = active;
+        if !finished.is_empty()
+        {
+            self.clear_marks();
+            self.clamp_selection();
+        }
+        finished
+    }
+
+    // Swaps the selected task with its neighbour, the selection and marks move along with it
+    pub fn move_selected(&mut self, up: bool) -> bool
+    {
+        let Some(index) = self.list_state.selected().filter(|index| *index < self.tasks.len()) else
+        {
+            return false;
+        };
+        let Some(target) = (if up { index.checked_sub(1) } else { Some(index + 1) }).filter(|target| *target < self.tasks.len()) else
+        {
+            return false;
+        };
+
+        self.snapshot();
+        self.tasks.swap(index, target);
+        let (index_marked, target_marked) = (self.marked.remove(&index), self.marked.remove(&target));
+        if index_marked
+        {
+            self.marked.insert(target);
+        }
+        if target_marked
+        {
+            self.marked.insert(index);
+        }
+        self.list_state.select(Some(target));
+        true
+    }
+
+    // Starts the timer on the selected task and stops any other, or stops it when it already runs
+    pub fn toggle_timer(&mut self) -> bool
+    {
+        let Some(index) = self.list_state.selected().filter(|index| *index < self.tasks.len()) else
+        {
+            return false;
+        };
+        let now: DateTime<Local> = Local::now();
+        if self.tasks[index].timer_running()
+        {
+            self.tasks[index].stop_timer(now);
+            return true;
+        }
+        for task in self.tasks.iter_mut()
+        {
+            task.stop_timer(now);
+        }
+        self.tasks[index].start_timer(now);
+        true
+    }
+
+    pub fn running_timer(&self) -> Option<&Task>
+    {
+        self.tasks.iter().find(|task| task.timer_running())
+    }
+
+    pub fn clamp_selection(&mut self)
+    {
+        if self.tasks.is_empty()
+        {
+            self.list_state.select(None);
+        } else if let Some(index) = self.list_state.selected() {
+            self.list_state.select(Some(index.min(self.tasks.len() - 1)));
+        }
+    }
+}
+
+pub fn run(command: TasksCli, data: &Data)
+{
+    match command
+    {
+        TasksCli::Log { by } =>
+        {
+            let tasks: Vec<&Task> = data.tasks.iter()
+                .flat_map(|tasks| tasks.tasks.iter())
+                .chain(data.archive.entries.iter().map(|entry| &entry.task))
+                .collect();
+            time_tracking::print_log(&tasks, by);
+        }
+        TasksCli::List { query } =>
+        {
+            let tasks: Vec<Task> = storage::with(|storage| storage.query_tasks(&query))
+                .unwrap_or_else(|error| storage::refuse(error));
+            for task in tasks
+            {
+                println!("#{:<5} {:<12} {:<7} {}", task.id, task.status.to_string(), task.priority.to_string(), task.task);
+            }
+        }
+        TasksCli::Set { id, priority, project, due } =>
+        {
+            let query: TaskQuery = TaskQuery { id: Some(id), ..TaskQuery::default() };
+            let Some(mut task) = storage::with(|storage| storage.query_tasks(&query))
+                .unwrap_or_else(|error| storage::refuse(error))
+                .pop()
+            else
+            {
+                eprintln!("There is no task #{}", id);
+                return;
+            };
+            if let Some(priority) = priority
+            {
+                task.priority = priority;
+            }
+            if let Some(project) = project
+            {
+                task.project = Some(project).filter(|project| !project.is_empty());
+            }
+            if due.is_some()
+            {
+                task.due = due;
+            }
+            storage::with(|storage| storage.update_task(&task)).unwrap_or_else(|error| storage::refuse(error));
+            println!("Changed #{} {}", task.id, task.task);
+        }
+    }
 }
 
 // Writing the new tasks to the file, and replacing the whole file
-pub fn write_tasks(tasks: &Tasks)
+pub fn write_tasks(tasks: &mut Tasks)
 {
-    let path = generate_path!(files::base_path(), tasks);
+    if files::shared()
+    {
+        merge::merge_tasks_with_disk(tasks);
+    }
+    save_tasks(tasks);
+}
+
+// Writes the tasks as they are, over whatever is on disk
+pub fn save_tasks(tasks: &mut Tasks)
+{
+    storage::with(|storage| storage.save_tasks(tasks))
+        .unwrap_or_else(|error| panic!("Failed to save tasks: {}", error));
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::commands::archive::{self, Archive, ArchiveReason};
+
+    fn tasks(titles: &[&str]) -> Tasks
+    {
+        let mut tasks: Tasks = Tasks::default();
+        for title in titles
+        {
+            tasks.add(Task::new(title.to_string(), Priority::Medium, String::new()));
+        }
+        tasks
+    }
+
+    #[test]
+    fn deleting_a_task_stops_its_timer()
+    {
+        let mut tasks: Tasks = tasks(&["Timed", "Other"]);
+        tasks.tasks[0].start_timer(Local::now());
+        tasks.list_state.select(Some(0));
+
+        let removed: Vec<Task> = tasks.bulk_delete();
+        assert_eq!(removed.len(), 1);
+        assert!(!removed[0].timer_running());
+        assert_eq!(tasks.tasks.len(), 1);
+    }
+
+    #[test]
+    fn undo_takes_out_exactly_the_entries_it_archived()
+    {
+        let mut archive: Archive = Archive::default();
+        archive.add(vec![Task { id: 90, ..Task::new("Archived before".to_string(), Priority::Low, String::new()) }], ArchiveReason::Deleted);
 
-    let toml_str = toml::to_string_pretty(&tasks)
-        .expect("Failed to serialize tasks");
+        let mut tasks: Tasks = tasks(&["Deleted", "Kept"]);
+        tasks.list_state.select(Some(0));
+        let deleted: Vec<Task> = tasks.bulk_delete();
+        archive::archive_deleted(&mut tasks, &mut archive, deleted);
+        // Something else changed the archive in between, like a restore
+        archive.entries.remove(0);
+        archive.add(vec![Task { id: 91, ..Task::new("Archived after".to_string(), Priority::Low, String::new()) }], ArchiveReason::Deleted);
 
-    std::fs::write(&path, toml_str).expect("Failed to write TOML file");
+        let archived: Vec<ArchiveKey> = tasks.undo().unwrap();
+        archive.remove_entries(&archived);
+        let left: Vec<&str> = archive.entries.iter().map(|entry| entry.task.task.as_str()).collect();
+        assert_eq!(left, ["Archived after"]);
+        assert_eq!(tasks.tasks.len(), 2);
+    }
 }
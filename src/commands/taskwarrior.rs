@@ -0,0 +1,228 @@
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+
+use crate::{
+    commands::{
+        import,
+        tasks::{self, Task},
+    },
+    structs::{Priority, Status},
+};
+
+
+// A task as `task export` writes it and `task import` reads it. Fields it has that the dashboard
+// does not, like urgency, are left out and Taskwarrior fills them in again
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct TaskwarriorTask
+{
+    pub uuid: String,
+    pub description: String,
+    pub status: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct Annotation
+{
+    pub entry: String,
+    pub description: String,
+}
+
+// Taskwarrior keeps its times in UTC, like 20250131T120000Z
+const TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+fn parse_time(time: &str) -> Option<DateTime<Local>>
+{
+    let time: NaiveDateTime = NaiveDateTime::parse_from_str(time, TIME_FORMAT).ok()?;
+    Some(Utc.from_utc_datetime(&time).with_timezone(&Local))
+}
+
+fn format_time(time: DateTime<Local>) -> String
+{
+    time.with_timezone(&Utc).format(TIME_FORMAT).to_string()
+}
+
+// Takes both the JSON array of newer versions and the object per line of older ones
+pub fn parse(content: &str) -> Result<Vec<Task>, String>
+{
+    let exported: Vec<TaskwarriorTask> = if content.trim_start().starts_with('[')
+    {
+        serde_json::from_str(content).map_err(|error| format!("Not a Taskwarrior export: {}", error))?
+    } else {
+        content.lines()
+            .map(|line| line.trim().trim_end_matches(','))
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_str(line).map_err(|error| format!("Not a Taskwarrior export: {}", error)))
+            .collect::<Result<_, _>>()?
+    };
+    Ok(exported.into_iter().filter_map(to_task).collect())
+}
+
+// None for the templates of recurring tasks, their instances come along as tasks of their own
+fn to_task(exported: TaskwarriorTask) -> Option<Task>
+{
+    let status: Status = match exported.status.as_str()
+    {
+        "completed" => Status::Done,
+        "deleted" => Status::Cancelled,
+        "recurring" => return None,
+        _ if exported.start.is_some() => Status::InProgress,
+        _ => Status::Todo,
+    };
+    let priority: Priority = exported.priority.as_deref().and_then(import::parse_priority).unwrap_or(Priority::Medium);
+    let annotations: Vec<String> = exported.annotations.into_iter().map(|annotation| annotation.description).collect();
+
+    let mut task: Task = Task::new(exported.description, priority, annotations.join("\n"));
+    task.status = status;
+    task.tags = exported.tags;
+    task.project = exported.project;
+    task.due = exported.due.as_deref().and_then(parse_time).map(|due| due.date_naive());
+    task.created = exported.entry.as_deref().and_then(parse_time);
+    task.uuid = Some(exported.uuid);
+    Some(task)
+}
+
+// Every line of the description becomes an annotation. The task needs its UUID before this
+pub fn from_task(task: &Task) -> TaskwarriorTask
+{
+    let now: DateTime<Local> = Local::now();
+    let entry: DateTime<Local> = task.created.unwrap_or(now);
+    let finished_at: DateTime<Local> = task.time_entries.last().and_then(|time_entry| time_entry.end).unwrap_or(now);
+
+    let (status, start, end): (&str, Option<DateTime<Local>>, Option<DateTime<Local>>) = match task.status
+    {
+        Status::Todo => ("pending", None, None),
+        Status::InProgress => ("pending", Some(task.time_entries.first().map_or(entry, |time_entry| time_entry.start)), None),
+        Status::Done => ("completed", None, Some(finished_at)),
+        Status::Cancelled => ("deleted", None, Some(finished_at)),
+    };
+    let priority: &str = match task.priority
+    {
+        Priority::High => "H",
+        Priority::Medium => "M",
+        Priority::Low => "L",
+    };
+
+    TaskwarriorTask
+    {
+        uuid: task.uuid.clone().unwrap_or_else(tasks::new_uuid),
+        description: task.task.clone(),
+        status: status.to_string(),
+        entry: Some(format_time(entry)),
+        modified: Some(format_time(now)),
+        start: start.map(format_time),
+        end: end.map(format_time),
+        due: task.due
+            .and_then(|due| due.and_hms_opt(0, 0, 0))
+            .and_then(|due| due.and_local_timezone(Local).single())
+            .map(format_time),
+        priority: Some(priority.to_string()),
+        project: task.project.clone(),
+        tags: task.tags.clone(),
+        annotations: task.description.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Annotation { entry: format_time(entry), description: line.to_string() })
+            .collect(),
+    }
+}
+
+pub fn to_json(tasks: &[Task]) -> String
+{
+    let exported: Vec<TaskwarriorTask> = tasks.iter().map(from_task).collect();
+    let mut json: String = serde_json::to_string_pretty(&exported).unwrap_or_default();
+    json.push('\n');
+    json
+}
+
+#[cfg(test)]
+mod tests
+{
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::commands::time_tracking::TimeEntry;
+
+    fn time(hour: u32) -> DateTime<Local>
+    {
+        Local.with_ymd_and_hms(2025, 1, 2, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn round_trip_keeps_what_both_sides_know()
+    {
+        let mut task: Task = Task::new("Write docs".to_string(), Priority::High, "First note\n\nSecond note".to_string());
+        task.status = Status::InProgress;
+        task.tags = vec!["docs".to_string()];
+        task.project = Some("web".to_string());
+        task.due = NaiveDate::from_ymd_opt(2025, 1, 31);
+        task.created = Some(time(9));
+        task.uuid = Some("5f8a0c1e-0000-4000-8000-000000000001".to_string());
+        task.time_entries.push(TimeEntry { start: time(10), end: None });
+
+        let exported: TaskwarriorTask = from_task(&task);
+        assert_eq!(exported.status, "pending");
+        assert_eq!(exported.start, Some(format_time(time(10))));
+        assert_eq!(exported.annotations.len(), 2);
+
+        let back: Task = to_task(exported).unwrap();
+        assert_eq!(back.task, task.task);
+        assert_eq!(back.description, "First note\nSecond note");
+        assert_eq!(back.status, Status::InProgress);
+        assert_eq!(back.priority, Priority::High);
+        assert_eq!(back.tags, task.tags);
+        assert_eq!(back.project, task.project);
+        assert_eq!(back.due, task.due);
+        assert_eq!(back.created, task.created);
+        assert_eq!(back.uuid, task.uuid);
+
+        // A second trip changes nothing more
+        let again: Task = to_task(from_task(&back)).unwrap();
+        assert_eq!(toml::to_string(&again).unwrap(), toml::to_string(&back).unwrap());
+    }
+
+    #[test]
+    fn statuses_map_both_ways()
+    {
+        for (status, name) in [(Status::Todo, "pending"), (Status::Done, "completed"), (Status::Cancelled, "deleted")]
+        {
+            let mut task: Task = Task::new("Task".to_string(), Priority::Low, String::new());
+            task.status = status.clone();
+            let exported: TaskwarriorTask = from_task(&task);
+            assert_eq!(exported.status, name);
+            assert_eq!(to_task(exported).unwrap().status, status);
+        }
+    }
+
+    #[test]
+    fn parses_both_export_styles_and_skips_recurring_templates()
+    {
+        let array: &str = r#"[{"uuid":"a","description":"One","status":"pending","priority":"L"},
+            {"uuid":"b","description":"Template","status":"recurring"}]"#;
+        let tasks: Vec<Task> = parse(array).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].priority, Priority::Low);
+
+        let lines: &str = "{\"uuid\":\"a\",\"description\":\"One\",\"status\":\"pending\"},\n{\"uuid\":\"c\",\"description\":\"Two\",\"status\":\"completed\"}\n";
+        let tasks: Vec<Task> = parse(lines).unwrap();
+        assert_eq!(tasks.iter().map(|task| task.task.as_str()).collect::<Vec<_>>(), ["One", "Two"]);
+        assert_eq!(tasks[0].priority, Priority::Medium);
+        assert!(parse("not json").is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Local, NaiveDate, TimeDelta};
+
+use crate::commands::tasks::Task;
+
+
+// One stretch of work on a task, the end is missing while the timer runs
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+pub struct TimeEntry
+{
+    pub start: DateTime<Local>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Local>>,
+}
+
+impl TimeEntry
+{
+    pub fn duration(&self, now: DateTime<Local>) -> TimeDelta
+    {
+        self.end.unwrap_or(now) - self.start
+    }
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+pub enum LogGrouping
+{
+    Day,
+    Tag,
+}
+
+// Like "2h 05m", or "4m 12s" for short times
+pub fn format_duration(duration: TimeDelta) -> String
+{
+    let seconds: i64 = duration.num_seconds().max(0);
+    let (hours, minutes) = (seconds / 3600, (seconds % 3600) / 60);
+    if hours > 0
+    {
+        format!("{}h {:02}m", hours, minutes)
+    } else {
+        format!("{}m {:02}s", minutes, seconds % 60)
+    }
+}
+
+// Like "01:02:03", for the running timer
+pub fn format_clock(duration: TimeDelta) -> String
+{
+    let seconds: i64 = duration.num_seconds().max(0);
+    format!("{:02}:{:02}:{:02}", seconds / 3600, (seconds % 3600) / 60, seconds % 60)
+}
+
+// Prints the tracked time per day or per tag, with the tasks inside every group
+pub fn print_log(tasks: &[&Task], grouping: LogGrouping)
+{
+    let now: DateTime<Local> = Local::now();
+    let mut groups: BTreeMap<String, BTreeMap<String, TimeDelta>> = BTreeMap::new();
+
+    for task in tasks
+    {
+        for entry in &task.time_entries
+        {
+            let keys: Vec<String> = match grouping
+            {
+                LogGrouping::Day =>
+                {
+                    let day: NaiveDate = entry.start.date_naive();
+                    vec![day.to_string()]
+                }
+                LogGrouping::Tag if task.tags.is_empty() => vec!["(untagged)".to_string()],
+                LogGrouping::Tag => task.tags.clone(),
+            };
+            for key in keys
+            {
+                *groups.entry(key)
+                    .or_default()
+                    .entry(task.task.clone())
+                    .or_insert(TimeDelta::zero()) += entry.duration(now);
+            }
+        }
+    }
+
+    if groups.is_empty()
+    {
+        println!("No time has been tracked yet");
+        return;
+    }
+
+    for (group, tasks) in groups
+    {
+        let total: TimeDelta = tasks.values().fold(TimeDelta::zero(), |total, duration| total + *duration);
+        println!("{}  {}", group, format_duration(total));
+        for (task, duration) in tasks
+        {
+            println!("    {:<40}  {}", task, format_duration(duration));
+        }
+    }
+}
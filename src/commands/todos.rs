@@ -0,0 +1,109 @@
+use std::{
+    fs,
+    path::Path,
+};
+
+use ignore::WalkBuilder;
+
+
+// A marker comment in the source, like "// TODO: handle errors"
+#[derive(Debug, Clone)]
+pub struct CodeTodo
+{
+    pub marker: String,
+    // Path relative to the project, with the line counted from 1
+    pub path: String,
+    pub line: usize,
+    pub text: String,
+    // The whole line the marker is on
+    pub context: String,
+}
+
+impl CodeTodo
+{
+    pub fn location(&self) -> String
+    {
+        format!("{}:{}", self.path, self.line)
+    }
+}
+
+// Walks the project the same way git sees it, .gitignore and hidden files are skipped
+pub fn scan(project: &Path, markers: &[String]) -> Vec<CodeTodo>
+{
+    let mut todos: Vec<CodeTodo> = Vec::new();
+    let walker = WalkBuilder::new(project)
+        .require_git(false)
+        .build();
+
+    for entry in walker.flatten()
+    {
+        if !entry.file_type().is_some_and(|file_type| file_type.is_file())
+        {
+            continue;
+        }
+        // Binary files are not valid utf-8 and are left out
+        let Ok(content) = fs::read_to_string(entry.path()) else
+        {
+            continue;
+        };
+        let path: String = entry.path().strip_prefix(project).unwrap_or(entry.path()).display().to_string();
+
+        for (number, line) in content.lines().enumerate()
+        {
+            if let Some((marker, text)) = find_marker(line, markers)
+            {
+                todos.push(CodeTodo
+                {
+                    marker: marker.to_string(),
+                    path: path.clone(),
+                    line: number + 1,
+                    text,
+                    context: line.trim().to_string(),
+                });
+            }
+        }
+    }
+    todos.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+    todos
+}
+
+// The first marker on the line that is a whole word, and the text after it
+fn find_marker<'a>(line: &str, markers: &'a [String]) -> Option<(&'a str, String)>
+{
+    markers.iter()
+        .filter(|marker| !marker.is_empty())
+        .filter_map(|marker|
+        {
+            line.match_indices(marker.as_str())
+                .find(|(start, _)|
+                {
+                    let before: Option<char> = line[..*start].chars().next_back();
+                    let after: Option<char> = line[start + marker.len()..].chars().next();
+                    !before.is_some_and(is_word) && !after.is_some_and(is_word)
+                })
+                .map(|(start, _)| (start, marker.as_str()))
+        })
+        .min_by_key(|(start, _)| *start)
+        .map(|(start, marker)|
+        {
+            // Drops things like "(name):" and the end of block comments
+            let rest: &str = &line[start + marker.len()..];
+            let rest: &str = match rest.strip_prefix('(').and_then(|rest| rest.split_once(')'))
+            {
+                Some((_, rest)) => rest,
+                None => rest,
+            };
+            let text: String = rest.trim_start_matches([':', ' ', '-'])
+                .trim_end()
+                .trim_end_matches("*/")
+                .trim_end_matches("-->")
+                .trim()
+                .to_string();
+            (marker, text)
+        })
+}
+
+fn is_word(c: char) -> bool
+{
+    c.is_alphanumeric() || c == '_'
+}
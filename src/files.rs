@@ -1,12 +1,40 @@
-use std::{env, fs::{self, File}, path::{PathBuf}};
-use toml;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    fs::{self, File},
+    path::PathBuf,
+    io::Write,
+    sync::{atomic::{AtomicBool, Ordering}, mpsc::{self, Receiver}, Mutex},
+};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::de::DeserializeOwned;
 
 use crate::{
+    commands,
     Data,
     structs,
 };
 
+pub mod directory;
+pub mod migrations;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod storage;
+
+
+// What each file held the last time it was read or written, as it would be written now.
+// Lets a change on disk be told apart from our own writes, and is the base of a three-way merge
+static SYNCED: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
 
+// Held for as long as the dashboard runs, the operating system lets go of it when the process ends
+static LOCK: Mutex<Option<File>> = Mutex::new(None);
+// Another dashboard has the lock, so saves merge with the files first
+static SHARED: AtomicBool = AtomicBool::new(false);
+const LOCK_FILE: &str = "dashboard.lock";
+
+
+#[allow(clippy::needless_return)]
 pub fn check_dir_valid() -> Result<PathBuf, ()>
 {
     let path: PathBuf = env::current_dir().expect("couldn't open path");
@@ -34,20 +62,121 @@ pub fn check_dir_valid() -> Result<PathBuf, ()>
 
 
 
+#[allow(clippy::needless_return)]
 pub fn read_data() -> Data
 {
     let dashboard_path: PathBuf = base_path();
     let mut data: Data = Data::new();
 
+    // Tasks and settings can live in a database, the rest is always TOML
+    storage::init();
+    storage::with(|storage|
+    {
+        data.tasks = storage.load_tasks().unwrap_or_else(|error| storage::refuse(error));
+        data.settings = storage.load_settings().unwrap_or_else(|error| storage::refuse(error)).unwrap_or_else(structs::Settings::new);
+    });
+
     get_files!(
         dashboard_path,
         data,
-        tasks => commands::tasks::Tasks => None,
-        settings => structs::Settings => structs::Settings::new(),
+        archive => commands::archive::Archive => commands::archive::Archive::default(),
+        overview => commands::overview::Overview => None,
+        commands => commands::runner::ProjectCommands => commands::runner::ProjectCommands::default(),
+        runs => commands::runner::RunHistory => commands::runner::RunHistory::default(),
     );
+    if let Some(tasks) = data.tasks.as_mut()
+    {
+        tasks.assign_ids();
+        set_synced("tasks", toml::to_string_pretty(tasks).unwrap_or_default());
+    }
+    set_synced("archive", toml::to_string_pretty(&data.archive).unwrap_or_default());
+    set_synced("runs", toml::to_string_pretty(&data.runs).unwrap_or_default());
     return data
 }
 
+// Reads a file from .dashboard, None when it is missing or does not parse
+pub fn read_toml<T>(name: &str) -> Option<T>
+where
+    T: DeserializeOwned,
+{
+    let mut path: PathBuf = base_path();
+    path.push(format!("{}.toml", name));
+    let (table, _) = migrations::upgrade(name, &fs::read_to_string(path).ok()?).ok()?;
+    table.try_into().ok()
+}
+
+// Writes the content with the current version in front of it
+pub fn write_file(name: &str, content: String)
+{
+    let mut path: PathBuf = base_path();
+    path.push(format!("{}.toml", name));
+    fs::write(&path, format!("version = {}\n\n{}", migrations::VERSION, content)).expect("Failed to write TOML file");
+    set_synced(name, content);
+}
+
+// Takes the lock in .dashboard, or goes into shared mode when another dashboard has it
+pub fn lock_dashboard()
+{
+    let mut path: PathBuf = base_path();
+    path.push(LOCK_FILE);
+
+    // The lock belongs to this machine, so it is kept out of git
+    let mut ignore_path: PathBuf = base_path();
+    ignore_path.push(".gitignore");
+    let ignored: String = fs::read_to_string(&ignore_path).unwrap_or_default();
+    if !ignored.lines().any(|line| line.trim() == LOCK_FILE)
+    {
+        let separator: &str = if ignored.is_empty() || ignored.ends_with('\n') { "" } else { "\n" };
+        let _ = fs::write(&ignore_path, format!("{}{}{}\n", ignored, separator, LOCK_FILE));
+    }
+
+    let file = File::options().read(true).write(true).create(true).truncate(false).open(&path);
+    match file
+    {
+        Ok(mut file) if file.try_lock().is_ok() =>
+        {
+            // The process id tells the other dashboards who has it
+            let _ = file.set_len(0);
+            let _ = write!(file, "{}", std::process::id());
+            *LOCK.lock().unwrap() = Some(file);
+        }
+        _ => SHARED.store(true, Ordering::Relaxed),
+    }
+}
+
+pub fn shared() -> bool
+{
+    SHARED.load(Ordering::Relaxed)
+}
+
+// The process id of the dashboard with the lock
+pub fn lock_holder() -> Option<String>
+{
+    let mut path: PathBuf = base_path();
+    path.push(LOCK_FILE);
+    fs::read_to_string(path).ok()
+        .map(|holder| holder.trim().to_string())
+        .filter(|holder| !holder.is_empty())
+}
+
+// Whether the value differs from what was last read or written
+pub fn is_unsaved<T>(name: &str, value: &T) -> bool
+where
+    T: serde::Serialize,
+{
+    toml::to_string_pretty(value).ok() != synced(name)
+}
+
+pub fn set_synced(name: &str, content: String)
+{
+    SYNCED.lock().unwrap().insert(name.to_string(), content);
+}
+
+pub fn synced(name: &str) -> Option<String>
+{
+    SYNCED.lock().unwrap().get(name).cloned()
+}
+
 
 
 pub fn base_path() -> PathBuf
@@ -69,3 +198,54 @@ pub fn ensure_file_exists(path: &PathBuf) -> Result<(), ()>
     }
     Ok(())
 }
+
+// Watches .dashboard for files changed by editors or git while the dashboard is open
+pub struct DashboardWatcher
+{
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<notify::Result<notify::Event>>,
+}
+
+impl DashboardWatcher
+{
+    pub fn new() -> notify::Result<Self>
+    {
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher: RecommendedWatcher = notify::recommended_watcher(sender)?;
+        // Recursive for the files in .dashboard/tasks when there is a file per task
+        watcher.watch(&base_path(), RecursiveMode::Recursive)?;
+        Ok(Self { _watcher: watcher, receiver })
+    }
+
+    // The names of the files that changed since the last call, like "tasks" for tasks.toml
+    pub fn changed_files(&self) -> BTreeSet<String>
+    {
+        let mut changed: BTreeSet<String> = BTreeSet::new();
+        for event in self.receiver.try_iter().flatten()
+        {
+            // Removed counts too, a task file deleted by git would otherwise be written back on the next save
+            if !(event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove())
+            {
+                continue;
+            }
+            for path in event.paths.iter().filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+            {
+                // Backups are only written by the dashboard itself
+                if path.parent().is_some_and(|parent| parent.ends_with("backup"))
+                {
+                    continue;
+                }
+                if path.parent().is_some_and(|parent| parent.ends_with("tasks"))
+                {
+                    changed.insert("tasks".to_string());
+                    continue;
+                }
+                if let Some(name) = path.file_stem().and_then(|stem| stem.to_str())
+                {
+                    changed.insert(name.to_string());
+                }
+            }
+        }
+        changed
+    }
+}
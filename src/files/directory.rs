@@ -0,0 +1,206 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use crate::{
+    commands::tasks::{Task, Tasks},
+    files::{self, migrations, storage::{Storage, StorageError, TomlStorage}},
+    structs::Settings,
+};
+
+
+// A file per task in .dashboard/tasks, so changing one task only changes its own file in git.
+// The files are named by the task's UUID, so tasks added on two branches never land in the same file.
+// The order of the list and the next id are in tasks/order.toml, which only changes when tasks are added, removed or moved
+pub struct DirectoryStorage
+{
+    // The file name and content of each task when it was last read or written, unchanged tasks are not written again
+    written: Mutex<BTreeMap<u64, (String, String)>>,
+}
+
+pub const ORDER_FILE: &str = "order.toml";
+
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct Order
+{
+    #[serde(default)]
+    pub next_id: u64,
+    #[serde(default)]
+    pub order: Vec<u64>,
+}
+
+pub fn tasks_dir() -> PathBuf
+{
+    let mut path: PathBuf = files::base_path();
+    path.push("tasks");
+    path
+}
+
+fn task_path(name: &str) -> PathBuf
+{
+    let mut path: PathBuf = tasks_dir();
+    path.push(format!("{}.toml", name));
+    path
+}
+
+// Tasks from before every task had a UUID keep the file named by their id
+fn file_name(task: &Task) -> String
+{
+    task.uuid.clone().unwrap_or_else(|| task.id.to_string())
+}
+
+fn task_content(task: &Task) -> Result<String, StorageError>
+{
+    Ok(format!("version = {}\n\n{}", migrations::VERSION, toml::to_string_pretty(task).map_err(failed)?))
+}
+
+fn read_task(path: &Path) -> Result<(Task, String), StorageError>
+{
+    let content: String = fs::read_to_string(path).map_err(failed)?;
+    let invalid = || StorageError::Failed(format!("{} is not a task", path.display()));
+    let task: Task = match migrations::upgrade("task", &content)
+    {
+        Ok((table, _)) => table.try_into().map_err(|_| invalid())?,
+        Err(migrations::LoadError::Newer(version)) =>
+        {
+            let name: String = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            return Err(StorageError::Newer { file: format!("tasks/{}", name), version });
+        }
+        Err(migrations::LoadError::Invalid) => return Err(invalid()),
+    };
+    Ok((task, content))
+}
+
+fn failed(error: impl ToString) -> StorageError
+{
+    StorageError::Failed(error.to_string())
+}
+
+impl DirectoryStorage
+{
+    pub fn new() -> Self
+    {
+        Self { written: Mutex::new(BTreeMap::new()) }
+    }
+
+    fn read_order() -> Result<Order, StorageError>
+    {
+        let mut path: PathBuf = tasks_dir();
+        path.push(ORDER_FILE);
+        let Ok(content) = fs::read_to_string(&path) else
+        {
+            return Ok(Order::default());
+        };
+        match migrations::upgrade("order", &content)
+        {
+            Ok((table, _)) => Ok(table.try_into().unwrap_or_default()),
+            Err(migrations::LoadError::Newer(version)) => Err(StorageError::Newer { file: format!("tasks/{}", ORDER_FILE), version }),
+            Err(migrations::LoadError::Invalid) => Ok(Order::default()),
+        }
+    }
+}
+
+impl Storage for DirectoryStorage
+{
+    fn load_tasks(&self) -> Result<Option<Tasks>, StorageError>
+    {
+        let order: Order = Self::read_order()?;
+        let mut loaded: Vec<(Task, String, String)> = Vec::new();
+        if let Ok(entries) = fs::read_dir(tasks_dir())
+        {
+            for path in entries.flatten().map(|entry| entry.path())
+            {
+                if path.file_name().is_some_and(|name| name == ORDER_FILE) || path.extension().is_none_or(|extension| extension != "toml")
+                {
+                    continue;
+                }
+                let (task, content) = read_task(&path)?;
+                let name: String = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                loaded.push((task, name, content));
+            }
+        }
+
+        // Tasks that are not in the order yet, like ones added on another branch, go at the end by id
+        let position = |task: &Task| order.order.iter().position(|id| *id == task.id).unwrap_or(usize::MAX);
+        loaded.sort_by(|(a, a_name, _), (b, b_name, _)|
+        {
+            (position(a), a.id, a.created, a_name).cmp(&(position(b), b.id, b.created, b_name))
+        });
+
+        // Two branches that each added a task got the same id for them, the one made first keeps it
+        let highest: u64 = loaded.iter().map(|(task, _, _)| task.id).max().unwrap_or(0);
+        let mut next_id: u64 = order.next_id.max(highest + 1);
+        let mut seen: BTreeSet<u64> = BTreeSet::new();
+        let mut written = self.written.lock().unwrap();
+        written.clear();
+        let mut tasks: Vec<Task> = Vec::new();
+        for (mut task, name, content) in loaded
+        {
+            if !seen.insert(task.id)
+            {
+                task.id = next_id;
+                next_id += 1;
+            }
+            written.insert(task.id, (name, content));
+            tasks.push(task);
+        }
+        Ok(Some(Tasks { next_id, tasks, ..Tasks::default() }))
+    }
+
+    fn save_tasks(&self, tasks: &Tasks) -> Result<(), StorageError>
+    {
+        fs::create_dir_all(tasks_dir()).map_err(failed)?;
+        let mut written = self.written.lock().unwrap();
+
+        for task in &tasks.tasks
+        {
+            let content: String = task_content(task)?;
+            // A task keeps the file it was read from, even after it got a UUID
+            let name: String = match written.get(&task.id)
+            {
+                Some((name, written_content)) if *written_content == content => continue,
+                Some((name, _)) => name.clone(),
+                None => file_name(task),
+            };
+            fs::write(task_path(&name), &content).map_err(failed)?;
+            written.insert(task.id, (name, content));
+        }
+
+        let removed: Vec<u64> = written.keys()
+            .filter(|id| !tasks.tasks.iter().any(|task| task.id == **id))
+            .copied()
+            .collect();
+        for id in removed
+        {
+            if let Some((name, _)) = written.remove(&id)
+            {
+                let _ = fs::remove_file(task_path(&name));
+            }
+        }
+        let order: Order = Order { next_id: tasks.next_id, order: tasks.tasks.iter().map(|task| task.id).collect() };
+        let order_content: String = format!("version = {}\n\n{}", migrations::VERSION, toml::to_string_pretty(&order).map_err(failed)?);
+        let mut order_path: PathBuf = tasks_dir();
+        order_path.push(ORDER_FILE);
+        if fs::read_to_string(&order_path).ok().as_ref() != Some(&order_content)
+        {
+            fs::write(order_path, order_content).map_err(failed)?;
+        }
+
+        files::set_synced("tasks", toml::to_string_pretty(tasks).unwrap_or_default());
+        Ok(())
+    }
+
+    // Settings stay in settings.toml, that is where the layout is picked
+    fn load_settings(&self) -> Result<Option<Settings>, StorageError>
+    {
+        TomlStorage.load_settings()
+    }
+
+    fn save_settings(&self, settings: &Settings) -> Result<(), StorageError>
+    {
+        TomlStorage.save_settings(settings)
+    }
+}
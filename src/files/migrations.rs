@@ -0,0 +1,143 @@
+use std::{fs, path::{Path, PathBuf}};
+
+use toml::Table;
+
+use crate::{files, structs::Settings};
+
+
+// The version written into every file in .dashboard, raise it when a file changes shape
+pub const VERSION: u32 = 1;
+
+// One step that brings a file from the version before `to` up to `to`
+struct Migration
+{
+    file: &'static str,
+    to: u32,
+    migrate: fn(&mut Table),
+}
+
+// Files without a step for a version only get their version raised
+const MIGRATIONS: &[Migration] = &[
+    Migration { file: "settings", to: 1, migrate: settings_default_colors },
+];
+
+pub enum LoadError
+{
+    // Not valid TOML, or not what the file should hold
+    Invalid,
+    // Written by a newer dashboard, reading it could lose what this one does not know about
+    Newer(u32),
+}
+
+// Takes the version out of the file and runs the steps it is missing, without touching the disk.
+// Files from before versions count as version 0
+pub fn upgrade(name: &str, content: &str) -> Result<(Table, u32), LoadError>
+{
+    let mut table: Table = toml::from_str(content).map_err(|_| LoadError::Invalid)?;
+    let version: u32 = match table.remove("version")
+    {
+        Some(toml::Value::Integer(version)) => u32::try_from(version).map_err(|_| LoadError::Invalid)?,
+        Some(_) => return Err(LoadError::Invalid),
+        None => 0,
+    };
+    if version > VERSION
+    {
+        return Err(LoadError::Newer(version));
+    }
+
+    for migration in MIGRATIONS.iter().filter(|migration| migration.file == name && migration.to > version)
+    {
+        (migration.migrate)(&mut table);
+    }
+    Ok((table, version))
+}
+
+// Like `upgrade`, and saves the upgraded file after putting a copy of the old one in .dashboard/backup
+pub fn load(name: &str, path: &Path, content: &str) -> Result<Table, LoadError>
+{
+    let (table, version) = upgrade(name, content)?;
+    // Empty files were just made, there is nothing to keep
+    if version < VERSION && !content.trim().is_empty()
+    {
+        let mut backup_path: PathBuf = files::base_path();
+        backup_path.push("backup");
+        fs::create_dir_all(&backup_path).expect("Could not make the backup directory");
+        backup_path.push(format!("{}.v{}.toml", name, version));
+        fs::copy(path, backup_path).expect("Could not back up the file before upgrading it");
+
+        let upgraded: String = toml::to_string_pretty(&table).expect("Failed to serialize upgraded file");
+        if only_version_changed(content, &table)
+        {
+            // The version goes in front of the old text, so its comments and layout stay
+            fs::write(path, format!("version = {}\n\n{}", VERSION, content)).expect("Failed to write upgraded file");
+            files::set_synced(name, upgraded);
+        } else {
+            files::write_file(name, upgraded);
+        }
+    }
+    Ok(table)
+}
+
+// Whether no step changed the file, so the upgraded table is still what the text says
+fn only_version_changed(content: &str, table: &Table) -> bool
+{
+    toml::from_str::<Table>(content).is_ok_and(|original| !original.contains_key("version") && original == *table)
+}
+
+// Settings without colors used to be thrown away as a whole
+fn settings_default_colors(table: &mut Table)
+{
+    if !table.contains_key("colors")
+        && let Ok(colors) = toml::Value::try_from(Settings::new().colors)
+    {
+        table.insert("colors".to_string(), colors);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn files_without_a_version_are_version_0()
+    {
+        let (table, version) = upgrade("commands", "test = \"cargo test\"\n").ok().unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(table.get("test").and_then(toml::Value::as_str), Some("cargo test"));
+
+        let (table, version) = upgrade("commands", &format!("version = {}\ntest = \"cargo test\"\n", VERSION)).ok().unwrap();
+        assert_eq!(version, VERSION);
+        assert!(!table.contains_key("version"));
+    }
+
+    #[test]
+    fn runs_the_missing_steps_of_the_file()
+    {
+        let (settings, _) = upgrade("settings", "").ok().unwrap();
+        assert!(settings.contains_key("colors"));
+        let (commands, _) = upgrade("commands", "").ok().unwrap();
+        assert!(!commands.contains_key("colors"));
+    }
+
+    #[test]
+    fn refuses_newer_and_invalid_files()
+    {
+        assert!(matches!(upgrade("tasks", &format!("version = {}\n", VERSION + 1)), Err(LoadError::Newer(version)) if version == VERSION + 1));
+        assert!(matches!(upgrade("tasks", "version = \"one\"\n"), Err(LoadError::Invalid)));
+        assert!(matches!(upgrade("tasks", "not = toml = at all"), Err(LoadError::Invalid)));
+    }
+
+    #[test]
+    fn keeps_the_text_when_only_the_version_changes()
+    {
+        let commands: &str = "# Runs every test\ntest = \"cargo test\"\n";
+        let (table, _) = upgrade("commands", commands).ok().unwrap();
+        assert!(only_version_changed(commands, &table));
+
+        let (table, _) = upgrade("settings", "# Defaults\n").ok().unwrap();
+        assert!(!only_version_changed("# Defaults\n", &table));
+        let (table, _) = upgrade("commands", "version = 0\n").ok().unwrap();
+        assert!(!only_version_changed("version = 0\n", &table));
+    }
+}
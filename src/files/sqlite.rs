@@ -0,0 +1,284 @@
+use std::{collections::{BTreeMap, BTreeSet}, path::Path, sync::Mutex};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::{
+    commands::tasks::{Task, Tasks},
+    files::{self, migrations, storage::{Storage, StorageError, TaskQuery, DATABASE_FILE}},
+    structs::Settings,
+};
+
+
+// Tasks as rows, for task lists that are too big to rewrite a file for every change.
+// The searchable fields have their own columns, the whole task is kept as TOML next to them
+pub struct SqliteStorage
+{
+    connection: Mutex<Connection>,
+    // The position and data of each row when it was last read or written, unchanged rows are not written again
+    written: Mutex<BTreeMap<u64, (usize, String)>>,
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS tasks (
+        id INTEGER PRIMARY KEY,
+        position INTEGER NOT NULL,
+        status TEXT NOT NULL,
+        project TEXT,
+        tags TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS tasks_status ON tasks (status);
+    CREATE INDEX IF NOT EXISTS tasks_project ON tasks (project);
+";
+
+impl From<rusqlite::Error> for StorageError
+{
+    fn from(error: rusqlite::Error) -> Self
+    {
+        StorageError::Failed(format!("{}: {}", DATABASE_FILE, error))
+    }
+}
+
+impl SqliteStorage
+{
+    pub fn open(path: &Path) -> Result<Self, StorageError>
+    {
+        let connection: Connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+
+        let version: Option<u32> = get_meta(&connection, "version")?.and_then(|version| version.parse().ok());
+        match version
+        {
+            Some(version) if version > migrations::VERSION => return Err(StorageError::Newer { file: DATABASE_FILE.to_string(), version }),
+            Some(_) => {},
+            None => set_meta(&connection, "version", &migrations::VERSION.to_string())?,
+        }
+        Ok(Self { connection: Mutex::new(connection), written: Mutex::new(BTreeMap::new()) })
+    }
+}
+
+fn get_meta(connection: &Connection, key: &str) -> Result<Option<String>, StorageError>
+{
+    let value: Option<String> = connection
+        .query_row("SELECT value FROM meta WHERE key = ?1", params![key], |row| row.get(0))
+        .optional()?;
+    Ok(value)
+}
+
+fn set_meta(connection: &Connection, key: &str, value: &str) -> Result<(), StorageError>
+{
+    connection.execute(
+        "INSERT INTO meta (key, value) VALUES (?1, ?2) ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+// Tags are stored like ",backend,urgent," so a single tag can be found with LIKE
+fn tag_column(task: &Task) -> String
+{
+    format!(",{},", task.tags.join(","))
+}
+
+fn status_column(task: &Task) -> String
+{
+    format!("{:?}", task.status)
+}
+
+fn task_data(task: &Task) -> Result<String, StorageError>
+{
+    toml::to_string(task).map_err(|error| StorageError::Failed(error.to_string()))
+}
+
+// LIKE treats % and _ as wildcards, in a tag they are plain characters
+fn escape_like(text: &str) -> String
+{
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+fn task_from_row(row: &rusqlite::Row) -> rusqlite::Result<String>
+{
+    row.get(0)
+}
+
+fn parse_tasks(rows: Vec<String>) -> Result<Vec<Task>, StorageError>
+{
+    rows.iter()
+        .map(|data| toml::from_str(data).map_err(|error| StorageError::Failed(format!("{}: {}", DATABASE_FILE, error))))
+        .collect()
+}
+
+impl Storage for SqliteStorage
+{
+    fn load_tasks(&self) -> Result<Option<Tasks>, StorageError>
+    {
+        let connection = self.connection.lock().unwrap();
+        let rows: Vec<String> = connection
+            .prepare("SELECT data FROM tasks ORDER BY position")?
+            .query_map([], task_from_row)?
+            .collect::<Result<_, _>>()?;
+        let next_id: u64 = get_meta(&connection, "next_id")?.and_then(|next_id| next_id.parse().ok()).unwrap_or(0);
+        let tasks: Vec<Task> = parse_tasks(rows.clone())?;
+
+        let mut written = self.written.lock().unwrap();
+        written.clear();
+        written.extend(tasks.iter().zip(rows).enumerate().map(|(position, (task, data))| (task.id, (position, data))));
+        Ok(Some(Tasks { next_id, tasks, ..Tasks::default() }))
+    }
+
+    fn save_tasks(&self, tasks: &Tasks) -> Result<(), StorageError>
+    {
+        let mut connection = self.connection.lock().unwrap();
+        let mut written = self.written.lock().unwrap();
+        let transaction = connection.transaction()?;
+        {
+            let mut upsert = transaction.prepare(
+                "INSERT INTO tasks (id, position, status, project, tags, data) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                ON CONFLICT (id) DO UPDATE SET position = excluded.position, status = excluded.status,
+                    project = excluded.project, tags = excluded.tags, data = excluded.data"
+            )?;
+            let mut move_row = transaction.prepare("UPDATE tasks SET position = ?2 WHERE id = ?1")?;
+            for (position, task) in tasks.tasks.iter().enumerate()
+            {
+                let data: String = task_data(task)?;
+                match written.get(&task.id)
+                {
+                    Some((written_position, written_data)) if *written_data == data =>
+                    {
+                        if *written_position != position
+                        {
+                            move_row.execute(params![task.id as i64, position as i64])?;
+                        }
+                    }
+                    _ =>
+                    {
+                        upsert.execute(params![task.id as i64, position as i64, status_column(task), task.project, tag_column(task), data])?;
+                    }
+                }
+                written.insert(task.id, (position, data));
+            }
+
+            // Only the rows of tasks that are gone, the database may also hold rows this dashboard never read
+            let kept: BTreeSet<u64> = tasks.tasks.iter().map(|task| task.id).collect();
+            let stored: Vec<u64> = transaction
+                .prepare("SELECT id FROM tasks")?
+                .query_map([], |row| row.get::<_, i64>(0))?
+                .map(|id| id.map(|id| id as u64))
+                .collect::<Result<_, _>>()?;
+            let mut delete = transaction.prepare("DELETE FROM tasks WHERE id = ?1")?;
+            for id in stored.into_iter().filter(|id| !kept.contains(id))
+            {
+                delete.execute(params![id as i64])?;
+                written.remove(&id);
+            }
+        }
+        set_meta(&transaction, "next_id", &tasks.next_id.to_string())?;
+        transaction.commit()?;
+
+        // Lets the dashboard see that everything is saved
+        files::set_synced("tasks", toml::to_string_pretty(tasks).unwrap_or_default());
+        Ok(())
+    }
+
+    fn load_settings(&self) -> Result<Option<Settings>, StorageError>
+    {
+        let connection = self.connection.lock().unwrap();
+        Ok(get_meta(&connection, "settings")?.and_then(|settings| toml::from_str(&settings).ok()))
+    }
+
+    fn save_settings(&self, settings: &Settings) -> Result<(), StorageError>
+    {
+        let connection = self.connection.lock().unwrap();
+        let settings: String = toml::to_string_pretty(settings).map_err(|error| StorageError::Failed(error.to_string()))?;
+        set_meta(&connection, "settings", &settings)
+    }
+
+    fn query_tasks(&self, query: &TaskQuery) -> Result<Vec<Task>, StorageError>
+    {
+        let connection = self.connection.lock().unwrap();
+        let status: Option<String> = query.status.as_ref().map(|status| format!("{:?}", status));
+        let tag: Option<String> = query.tag.as_ref().map(|tag| format!("%,{},%", escape_like(tag)));
+        let rows: Vec<String> = connection
+            .prepare(
+                "SELECT data FROM tasks
+                WHERE (?1 IS NULL OR id = ?1) AND (?2 IS NULL OR status = ?2) AND (?3 IS NULL OR project = ?3) AND (?4 IS NULL OR tags LIKE ?4 ESCAPE '\\')
+                ORDER BY position"
+            )?
+            .query_map(params![query.id.map(|id| id as i64), status, query.project, tag], task_from_row)?
+            .collect::<Result<_, _>>()?;
+        parse_tasks(rows)
+    }
+
+    fn update_task(&self, task: &Task) -> Result<bool, StorageError>
+    {
+        let connection = self.connection.lock().unwrap();
+        let data: String = task_data(task)?;
+        let changed: usize = connection.execute(
+            "UPDATE tasks SET status = ?2, project = ?3, tags = ?4, data = ?5 WHERE id = ?1",
+            params![task.id as i64, status_column(task), task.project, tag_column(task), data],
+        )?;
+        if let Some((_, written_data)) = self.written.lock().unwrap().get_mut(&task.id)
+        {
+            *written_data = data;
+        }
+        Ok(changed > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::structs::Priority;
+
+    fn tasks(titles: &[(&str, &[&str])]) -> Tasks
+    {
+        let mut tasks: Tasks = Tasks::default();
+        for (title, tags) in titles
+        {
+            let mut task: Task = Task::new(title.to_string(), Priority::Medium, String::new());
+            task.tags = tags.iter().map(|tag| tag.to_string()).collect();
+            tasks.add(task);
+        }
+        tasks
+    }
+
+    fn titles(tasks: &[Task]) -> Vec<&str>
+    {
+        tasks.iter().map(|task| task.task.as_str()).collect()
+    }
+
+    #[test]
+    fn saves_only_what_changed()
+    {
+        let storage: SqliteStorage = SqliteStorage::open(Path::new(":memory:")).unwrap();
+        let mut saved: Tasks = tasks(&[("First", &[]), ("Second", &[]), ("Third", &[])]);
+        storage.save_tasks(&saved).unwrap();
+
+        let loaded: Tasks = storage.load_tasks().unwrap().unwrap();
+        assert_eq!(titles(&loaded.tasks), ["First", "Second", "Third"]);
+
+        saved.tasks.swap(0, 2);
+        saved.tasks[1].task = "Second, edited".to_string();
+        let removed: Task = saved.tasks.pop().unwrap();
+        storage.save_tasks(&saved).unwrap();
+
+        let loaded: Tasks = storage.load_tasks().unwrap().unwrap();
+        assert_eq!(titles(&loaded.tasks), ["Third", "Second, edited"]);
+        assert_eq!(loaded.next_id, saved.next_id);
+        assert!(!storage.written.lock().unwrap().contains_key(&removed.id));
+    }
+
+    #[test]
+    fn tag_query_does_not_use_wildcards()
+    {
+        let storage: SqliteStorage = SqliteStorage::open(Path::new(":memory:")).unwrap();
+        storage.save_tasks(&tasks(&[("Plain", &["ab"]), ("Underscore", &["a_b"]), ("Percent", &["100%"])])).unwrap();
+
+        let query = |tag: &str| storage.query_tasks(&TaskQuery { tag: Some(tag.to_string()), ..TaskQuery::default() }).unwrap();
+        assert_eq!(titles(&query("a_b")), ["Underscore"]);
+        assert_eq!(titles(&query("%")), Vec::<&str>::new());
+        assert_eq!(titles(&query("100%")), ["Percent"]);
+    }
+}
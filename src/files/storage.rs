@@ -0,0 +1,342 @@
+use std::{
+    fmt,
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    commands::tasks::{Task, Tasks},
+    files::{self, directory::{self, DirectoryStorage}, migrations},
+    structs::{Settings, Status, TaskLayout},
+};
+
+#[cfg(feature = "sqlite")]
+use crate::files::sqlite::SqliteStorage;
+
+
+// The storage the dashboard opened, every load and save of tasks and settings goes through it
+static STORAGE: Mutex<Option<Box<dyn Storage>>> = Mutex::new(None);
+pub const DATABASE_FILE: &str = "dashboard.db";
+
+// Where the tasks and settings are kept. The other files in .dashboard are always TOML
+pub trait Storage: Send
+{
+    // None when there is nothing stored yet, or it could not be read
+    fn load_tasks(&self) -> Result<Option<Tasks>, StorageError>;
+
+    fn save_tasks(&self, tasks: &Tasks) -> Result<(), StorageError>;
+
+    fn load_settings(&self) -> Result<Option<Settings>, StorageError>;
+
+    fn save_settings(&self, settings: &Settings) -> Result<(), StorageError>;
+
+    // Backends that can search without loading every task override this
+    fn query_tasks(&self, query: &TaskQuery) -> Result<Vec<Task>, StorageError>
+    {
+        let tasks: Vec<Task> = self.load_tasks()?.map(|tasks| tasks.tasks).unwrap_or_default();
+        Ok(tasks.into_iter().filter(|task| query.matches(task)).collect())
+    }
+
+    // Replaces the task with the same id, false when there is none
+    fn update_task(&self, task: &Task) -> Result<bool, StorageError>
+    {
+        let Some(mut tasks) = self.load_tasks()? else
+        {
+            return Ok(false);
+        };
+        let Some(index) = tasks.find(task.id) else
+        {
+            return Ok(false);
+        };
+        tasks.tasks[index] = task.clone();
+        self.save_tasks(&tasks)?;
+        Ok(true)
+    }
+}
+
+#[derive(Debug)]
+pub enum StorageError
+{
+    // Written by a newer dashboard
+    Newer { file: String, version: u32 },
+    Failed(String),
+}
+
+impl fmt::Display for StorageError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            StorageError::Newer { file, version } => write!(
+                f,
+                "{} is version {}, but this dashboard only knows up to version {}. Update the dashboard to open this project.",
+                file, version, migrations::VERSION
+            ),
+            StorageError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum StorageKind
+{
+    Toml,
+    // A TOML file per task
+    Directory,
+    Sqlite,
+}
+
+impl fmt::Display for StorageKind
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            StorageKind::Toml => write!(f, "TOML"),
+            StorageKind::Directory => write!(f, "a TOML file per task"),
+            StorageKind::Sqlite => write!(f, "SQLite"),
+        }
+    }
+}
+
+// Which tasks to get, the fields that are set all have to match
+#[derive(Debug, Default, Clone, clap::Args)]
+pub struct TaskQuery
+{
+    #[arg(skip)]
+    pub id: Option<u64>,
+
+    #[arg(long)]
+    pub status: Option<Status>,
+
+    #[arg(long)]
+    pub project: Option<String>,
+
+    #[arg(long)]
+    pub tag: Option<String>,
+}
+
+impl TaskQuery
+{
+    pub fn matches(&self, task: &Task) -> bool
+    {
+        self.id.is_none_or(|id| task.id == id)
+            && self.status.as_ref().is_none_or(|status| task.status == *status)
+            && self.project.as_ref().is_none_or(|project| task.project.as_ref() == Some(project))
+            && self.tag.as_ref().is_none_or(|tag| task.tags.contains(tag))
+    }
+}
+
+// A database means SQLite, otherwise the layout in settings.toml picks how the TOML is laid out
+pub fn detect() -> StorageKind
+{
+    if database_path().exists()
+    {
+        return StorageKind::Sqlite;
+    }
+    match files::read_toml::<Settings>("settings").map(|settings| settings.task_layout)
+    {
+        Some(TaskLayout::Directory) => StorageKind::Directory,
+        _ => StorageKind::Toml,
+    }
+}
+
+// The TOML layout the tasks are in right now, which lags behind the setting until they are converted
+fn layout_on_disk() -> StorageKind
+{
+    if directory::tasks_dir().exists() { StorageKind::Directory } else { StorageKind::Toml }
+}
+
+pub fn database_path() -> PathBuf
+{
+    let mut path: PathBuf = files::base_path();
+    path.push(DATABASE_FILE);
+    path
+}
+
+pub fn open(kind: StorageKind) -> Result<Box<dyn Storage>, StorageError>
+{
+    match kind
+    {
+        StorageKind::Toml => Ok(Box::new(TomlStorage)),
+        StorageKind::Directory => Ok(Box::new(DirectoryStorage::new())),
+        #[cfg(feature = "sqlite")]
+        StorageKind::Sqlite => Ok(Box::new(SqliteStorage::open(&database_path())?)),
+        #[cfg(not(feature = "sqlite"))]
+        StorageKind::Sqlite => Err(StorageError::Failed(
+            "This project keeps its tasks in SQLite, but this dashboard was built without it. Build it with `--features sqlite`.".to_string()
+        )),
+    }
+}
+
+// Opens the storage of the project, the dashboard can not do anything without it
+pub fn init()
+{
+    let mut kind: StorageKind = detect();
+    if kind != StorageKind::Sqlite && kind != layout_on_disk()
+    {
+        // The layout setting was changed. Another dashboard still uses the old layout, so that waits
+        if files::shared()
+        {
+            kind = layout_on_disk();
+        } else {
+            convert(layout_on_disk(), kind);
+        }
+    }
+
+    match open(kind)
+    {
+        Ok(storage) => *STORAGE.lock().unwrap() = Some(storage),
+        Err(error) => refuse(error),
+    }
+}
+
+pub fn with<R, F>(action: F) -> R
+where
+    F: FnOnce(&dyn Storage) -> R,
+{
+    let storage = STORAGE.lock().unwrap();
+    action(storage.as_deref().expect("The storage has not been opened"))
+}
+
+// The tasks as they are stored right now, None when they can not be read
+pub fn read_tasks() -> Option<Tasks>
+{
+    with(|storage| storage.load_tasks().ok().flatten())
+}
+
+pub fn refuse(error: StorageError) -> !
+{
+    eprintln!("{}", error);
+    std::process::exit(1);
+}
+
+// A file per kind of data in .dashboard, the way it has always been
+pub struct TomlStorage;
+
+impl TomlStorage
+{
+    fn load<T>(name: &str) -> Result<Option<T>, StorageError>
+    where
+        T: DeserializeOwned,
+    {
+        let mut path: PathBuf = files::base_path();
+        path.push(format!("{}.toml", name));
+        files::ensure_file_exists(&path).map_err(|_| StorageError::Failed(format!("Could not make {}", path.display())))?;
+        let content: String = fs::read_to_string(&path).map_err(|error| StorageError::Failed(error.to_string()))?;
+
+        match migrations::load(name, &path, &content)
+        {
+            Ok(table) => Ok(table.try_into().ok()),
+            Err(migrations::LoadError::Newer(version)) => Err(StorageError::Newer { file: format!("{}.toml", name), version }),
+            Err(migrations::LoadError::Invalid) => Ok(None),
+        }
+    }
+}
+
+impl Storage for TomlStorage
+{
+    fn load_tasks(&self) -> Result<Option<Tasks>, StorageError>
+    {
+        Self::load("tasks")
+    }
+
+    fn save_tasks(&self, tasks: &Tasks) -> Result<(), StorageError>
+    {
+        let toml_str = toml::to_string_pretty(&tasks)
+            .map_err(|error| StorageError::Failed(error.to_string()))?;
+        files::write_file("tasks", toml_str);
+        Ok(())
+    }
+
+    fn load_settings(&self) -> Result<Option<Settings>, StorageError>
+    {
+        Self::load("settings")
+    }
+
+    fn save_settings(&self, settings: &Settings) -> Result<(), StorageError>
+    {
+        let toml_str = toml::to_string_pretty(&settings)
+            .map_err(|error| StorageError::Failed(error.to_string()))?;
+        files::write_file("settings", toml_str);
+        Ok(())
+    }
+}
+
+// Copies the tasks and settings to the other backend, the old files go to .dashboard/backup
+pub fn migrate_storage(to: StorageKind)
+{
+    let from: StorageKind = detect();
+    if from == to
+    {
+        println!("The tasks are already kept in {}", to);
+        return;
+    }
+    if files::shared()
+    {
+        refuse(StorageError::Failed("Close the other dashboard on this project before moving its storage".to_string()));
+    }
+
+    let count: usize = convert(from, to);
+    println!("Moved {} tasks and the settings from {} to {}", count, from, to);
+}
+
+// Moves everything from one backend to the other, returns how many tasks were moved
+fn convert(from: StorageKind, to: StorageKind) -> usize
+{
+    let source: Box<dyn Storage> = open(from).unwrap_or_else(|error| refuse(error));
+    let tasks: Tasks = source.load_tasks().unwrap_or_else(|error| refuse(error)).unwrap_or_default();
+    let mut settings: Settings = match source.load_settings().unwrap_or_else(|error| refuse(error))
+    {
+        Some(settings) => settings,
+        None => Settings::new(),
+    };
+    drop(source);
+
+    // The setting has to match, or the next start would convert them back
+    match to
+    {
+        StorageKind::Toml => settings.task_layout = TaskLayout::File,
+        StorageKind::Directory => settings.task_layout = TaskLayout::Directory,
+        StorageKind::Sqlite => {},
+    }
+
+    let target: Box<dyn Storage> = open(to).unwrap_or_else(|error| refuse(error));
+    target.save_tasks(&tasks).unwrap_or_else(|error| refuse(error));
+    target.save_settings(&settings).unwrap_or_else(|error| refuse(error));
+    drop(target);
+
+    let mut backup_path: PathBuf = files::base_path();
+    backup_path.push("backup");
+    fs::create_dir_all(&backup_path).expect("Could not make the backup directory");
+    // settings.toml stays when both sides are TOML
+    let mut old_files: Vec<&str> = match from
+    {
+        StorageKind::Toml => vec!["tasks.toml"],
+        StorageKind::Directory => vec!["tasks"],
+        StorageKind::Sqlite => vec![DATABASE_FILE],
+    };
+    if from != StorageKind::Sqlite && to == StorageKind::Sqlite
+    {
+        old_files.push("settings.toml");
+    }
+
+    let suffix: String = format!("before-{}-{}", format!("{:?}", to).to_lowercase(), chrono::Local::now().format("%Y%m%d%H%M%S"));
+    for file in old_files
+    {
+        let mut path: PathBuf = files::base_path();
+        path.push(file);
+        if !path.exists()
+        {
+            continue;
+        }
+        let mut backup: PathBuf = backup_path.clone();
+        backup.push(format!("{}.{}", file, suffix));
+        fs::rename(path, backup).expect("Could not move the old storage to the backup directory");
+    }
+    tasks.tasks.len()
+}
@@ -16,10 +16,17 @@ macro_rules! get_files
     {{
         $(
             let path: std::path::PathBuf = generate_path!($base_path.clone(), $field);
-            crate::files::ensure_file_exists(&path).expect("Could not find or make file");
+            $crate::files::ensure_file_exists(&path).expect("Could not find or make file");
             
-            let toml_file: String = std::fs::read_to_string(path).expect("Could not read file");
-            $data.$field = toml::from_str(&toml_file).unwrap_or_else(|_| $default);
+            let toml_file: String = std::fs::read_to_string(&path).expect("Could not read file");
+            $data.$field = match $crate::files::migrations::load(stringify!($field), &path, &toml_file)
+            {
+                Ok(table) => table.try_into().unwrap_or_else(|_| $default),
+                Err($crate::files::migrations::LoadError::Newer(version)) => $crate::files::storage::refuse(
+                    $crate::files::storage::StorageError::Newer { file: format!("{}.toml", stringify!($field)), version }
+                ),
+                Err($crate::files::migrations::LoadError::Invalid) => $default,
+            };
         )*
 
     }};
@@ -37,7 +44,7 @@ macro_rules! draw_terminal
             $terminal.draw(|frame|
             {
                 $render_function(frame $(, $args)*);
-                crate::tui::render_log_popup(frame, $app_state.error_state.as_ref().unwrap(), &$data.settings.colors)
+                $crate::tui::render_log_popup(frame, $app_state.error_state.as_ref().unwrap(), &$data.settings.colors)
             }).unwrap();
         }
     }};
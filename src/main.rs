@@ -7,23 +7,47 @@ mod structs;
 mod commands;
 mod tui;
 
+use clap::Parser;
 use ratatui::DefaultTerminal;
 use structs::{
+    Cli,
+    CliCommand,
+    Command,
     Data,
 };
 
 fn main()
 {
     
-    //let mut cli: Cli = Cli::parse();
+    let cli: Cli = Cli::parse();
+    let command: Option<Command> = match cli.command
+    {
+        // git runs the merge driver in the middle of a merge, it only touches the files it is given
+        Some(CliCommand::MergeDriver { command }) => std::process::exit(commands::merge_driver::run(&command)),
+        Some(CliCommand::Dashboard(command)) => Some(command),
+        None => None,
+    };
     let path: PathBuf = files::check_dir_valid().expect("failed in checking the dirs");
-    println!("Hello, world! {}", path.display());
+    files::lock_dashboard();
     
     let mut data: Data = files::read_data();
 
+    if let Some(command) = command
+    {
+        if files::shared()
+        {
+            eprintln!("Another dashboard is open on this project, changes are merged with the files before they are saved");
+        }
+        commands::run(command, &mut data);
+        return;
+    }
+    println!("Hello, world! {}", path.display());
+
     let terminal: DefaultTerminal = ratatui::init();
     tui::start();
+    set_panic_function();
     tui::run(terminal, &mut data);
+    tui::stop();
     ratatui::restore();
 }
 
@@ -33,7 +57,8 @@ fn set_panic_function()
     panic::set_hook(Box::new(move |panic_info|
         {
             // Restore the terminal
-            let _ = ratatui::restore();
+            tui::stop();
+            ratatui::restore();
             // Call the original panic hook to preserve default panic behavior
             original_hook(panic_info);
         }));
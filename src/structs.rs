@@ -1,8 +1,7 @@
 use std::fmt;
 use clap::{self, Parser};
-use serde;
 
-use crate::{commands::tasks, tui::TuiColor};
+use crate::{commands::{archive, export, import, merge_driver, overview, runner, tasks}, files::storage, tui::TuiColor};
 
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = "A tool for checking and keeping track of your project")]
@@ -12,6 +11,54 @@ pub struct Cli
     //#[arg(short, long, num_args(0..=1), default_missing_value = "none")]
     //pub tasks: Option<tasks::TasksCli>,
 
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum CliCommand
+{
+    #[command(flatten)]
+    Dashboard(Command),
+
+    /// Let git merge the files in .dashboard by task instead of by line
+    MergeDriver
+    {
+        #[command(subcommand)]
+        command: merge_driver::MergeDriverCli,
+    },
+}
+
+// The subcommands that work on the loaded dashboard
+#[derive(Debug, clap::Subcommand)]
+pub enum Command
+{
+    /// Look at or clean up the archived tasks
+    Archive
+    {
+        #[command(subcommand)]
+        command: archive::ArchiveCli,
+    },
+
+    /// Reports about the tasks
+    Tasks
+    {
+        #[command(subcommand)]
+        command: tasks::TasksCli,
+    },
+
+    /// Write the tasks as Markdown, CSV, JSON or for Taskwarrior
+    Export(export::ExportCli),
+
+    /// Add tasks from todo.txt, a Markdown checklist, CSV, Taskwarrior or an export of this dashboard
+    Import(import::ImportCli),
+
+    /// Move the tasks and settings to another storage backend
+    MigrateStorage
+    {
+        #[arg(value_enum)]
+        to: storage::StorageKind,
+    },
 }
 
 // All data that should be found in the TOML files
@@ -20,17 +67,97 @@ pub struct Data
 {
     pub settings: Settings,
     pub tasks: Option<tasks::Tasks>,
-    //overview: Option<Overview>,
+    pub archive: archive::Archive,
+    pub overview: Option<overview::Overview>,
+    pub commands: runner::ProjectCommands,
+    pub runs: runner::RunHistory,
 }
 
 #[derive(Debug, serde::Deserialize, serde::Serialize, Default)]
 pub struct Settings
 {
     pub colors: TuiColor,
+
+    #[serde(default)]
+    pub pomodoro: PomodoroSettings,
+
+    #[serde(default)]
+    pub git: GitSettings,
+
+    #[serde(default)]
+    pub todos: TodoSettings,
+
+    // How the tasks are kept when they are in TOML, changing it converts them on the next start
+    #[serde(default)]
+    pub task_layout: TaskLayout,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskLayout
+{
+    // Every task in tasks.toml
+    #[default]
+    File,
+    // A file per task in .dashboard/tasks, so git can merge changes to different tasks
+    Directory,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct TodoSettings
+{
+    // Words the code scanner looks for, matched as whole words
+    pub markers: Vec<String>,
+}
+
+impl Default for TodoSettings
+{
+    fn default() -> Self
+    {
+        Self { markers: vec!["TODO".to_string(), "FIXME".to_string(), "HACK".to_string()] }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct GitSettings
+{
+    // How many of the last commits are shown
+    pub commit_count: usize,
+    pub refresh_seconds: u64,
+}
+
+impl Default for GitSettings
+{
+    fn default() -> Self
+    {
+        Self { commit_count: 20, refresh_seconds: 5 }
+    }
+}
+
+// Lengths are in minutes
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct PomodoroSettings
+{
+    pub work: u32,
+    pub short_break: u32,
+    pub long_break: u32,
+    // After how many pomodoros the long break comes
+    pub long_break_every: u32,
+}
+
+impl Default for PomodoroSettings
+{
+    fn default() -> Self
+    {
+        Self { work: 25, short_break: 5, long_break: 15, long_break_every: 4 }
+    }
 }
 
 // for the priority for the tasks
-#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, clap::ValueEnum)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq, clap::ValueEnum)]
 pub enum Priority
 {
     High,
@@ -76,12 +203,58 @@ impl Priority
     }
 }
 
+// The state a task is in
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq, clap::ValueEnum)]
+pub enum Status
+{
+    Todo,
+    InProgress,
+    Done,
+    Cancelled,
+}
+
+// For printing Status
+impl fmt::Display for Status
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let s = match self
+        {
+            Status::Todo => "Todo",
+            Status::InProgress => "In progress",
+            Status::Done => "Done",
+            Status::Cancelled => "Cancelled",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl Status
+{
+    // Used for picking a status with a single key
+    pub fn from_char(c: char) -> Option<Self>
+    {
+        match c.to_ascii_lowercase()
+        {
+            't' => Some(Status::Todo),
+            'i' => Some(Status::InProgress),
+            'd' => Some(Status::Done),
+            'c' => Some(Status::Cancelled),
+            _ => None,
+        }
+    }
+}
+
 impl Data 
 {
     pub fn new() -> Self 
     {
         Self { 
             tasks: None,
+            archive: archive::Archive::default(),
+            overview: None,
+            commands: runner::ProjectCommands::default(),
+            runs: runner::RunHistory::default(),
             settings: Settings::new(),
         }
     }
@@ -91,7 +264,14 @@ impl Settings
 {
     pub fn new() -> Self
     {
-        Self { colors: TuiColor { selected: TuiColor::SELECTED, default_text: TuiColor::DEFAULT_TEXT }, }
+        Self
+        {
+            colors: TuiColor { selected: TuiColor::SELECTED, default_text: TuiColor::DEFAULT_TEXT, marked: TuiColor::MARKED },
+            pomodoro: PomodoroSettings::default(),
+            git: GitSettings::default(),
+            todos: TodoSettings::default(),
+            task_layout: TaskLayout::default(),
+        }
     }
 }
 
@@ -1,3 +1,16 @@
+#[allow(clippy::module_inception)]
 mod tui;
 pub use tui::*;
+pub mod archive;
+pub mod commands;
+pub mod detail;
+pub mod events;
+pub mod export;
+pub mod git;
+pub mod overview;
+pub mod pomodoro;
+pub mod reload;
+pub mod report;
+pub mod screen;
 pub mod tasks;
+pub mod todos;
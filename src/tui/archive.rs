@@ -0,0 +1,130 @@
+use ratatui::{
+    crossterm::event::{self, KeyEvent},
+    layout::{Constraint, Direction, Layout},
+    prelude::Rect,
+    style::{Style, Stylize},
+    widgets::{self, Block, List, ListItem, Paragraph},
+    Frame,
+};
+
+use crate::{
+    commands::{
+        self,
+        archive::ArchivedTask,
+    },
+    structs::Data,
+    tui::{
+        self, AppState, TasksState
+    }
+};
+
+
+#[derive(Default)]
+pub struct ArchiveViewState
+{
+    pub search: String,
+    pub searching: bool,
+}
+
+pub fn handle_keys_archive(app_state: &mut AppState, key: KeyEvent, data: &mut Data, view_state: &mut ArchiveViewState)
+{
+    if view_state.searching
+    {
+        match key.code
+        {
+            event::KeyCode::Esc | event::KeyCode::Enter => view_state.searching = false,
+            event::KeyCode::Backspace => { view_state.search.pop(); }
+            event::KeyCode::Char(c) => view_state.search.push(c),
+            _ => {},
+        }
+        data.archive.list_state.select_first();
+        return;
+    }
+
+    let results: Vec<usize> = data.archive.search(&view_state.search);
+    match key.code
+    {
+        event::KeyCode::Esc =>
+        {
+            *view_state = ArchiveViewState::default();
+            app_state.current_state = tui::TuiState::Tasks(TasksState::Main);
+        }
+        event::KeyCode::Char('/') =>
+        {
+            view_state.searching = true;
+        }
+        event::KeyCode::Char('j') | event::KeyCode::Down =>
+        {
+            data.archive.list_state.select_next();
+        }
+        event::KeyCode::Char('k') | event::KeyCode::Up =>
+        {
+            data.archive.list_state.select_previous();
+        }
+        event::KeyCode::Char('r') | event::KeyCode::Enter =>
+        {
+            let Some(&index) = data.archive.list_state.selected().and_then(|selected| results.get(selected)) else
+            {
+                app_state.set_error("Nothing selected".to_string(), "No archived task has been selected".to_string(), tui::ErrorType::Warning);
+                return;
+            };
+            let task = data.archive.restore(index);
+            let tasks = data.tasks.get_or_insert_with(Default::default);
+            // The undo steps count on the archive only growing at the end
+            tasks.undo_stack.clear();
+            tasks.add(task);
+            commands::tasks::write_tasks(tasks);
+            commands::archive::write_archive(&mut data.archive);
+        }
+        _ => {},
+    }
+}
+
+pub fn render_archive(frame: &mut Frame, area: Rect, data: &mut Data, view_state: &ArchiveViewState)
+{
+    let chunks: [Rect; 2] = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(3), Constraint::Fill(1)])
+        .areas(area);
+
+    let search_style = if view_state.searching
+    {
+        Style::default().fg(data.settings.colors.selected)
+    } else {
+        Style::default().fg(data.settings.colors.default_text)
+    };
+    let search = Paragraph::new(view_state.search.as_str())
+        .block(Block::bordered().title("Search (/)").border_type(widgets::BorderType::Rounded))
+        .style(search_style);
+    frame.render_widget(search, chunks[0]);
+    if view_state.searching
+    {
+        frame.set_cursor_position((chunks[0].x + view_state.search.len() as u16 + 1, chunks[0].y + 1));
+    }
+
+    let results: Vec<usize> = data.archive.search(&view_state.search);
+    let items: Vec<ListItem> = results.iter()
+        .map(|index| ListItem::from(format_entry(&data.archive.entries[*index])))
+        .collect();
+    let list = List::new(items)
+        .block(Block::bordered()
+            .title(format!("Archive ({} of {})", results.len(), data.archive.entries.len()))
+            .title_bottom(" r/Enter: Restore | /: Search | Esc: Back ")
+            .border_type(widgets::BorderType::Rounded))
+        .fg(data.settings.colors.default_text)
+        .highlight_style(Style::default().fg(data.settings.colors.selected))
+        .highlight_symbol(">");
+    frame.render_stateful_widget(list, chunks[1], &mut data.archive.list_state);
+}
+
+fn format_entry(entry: &ArchivedTask) -> String
+{
+    format!(
+        "{}  {:<9}  {:<6}  {}",
+        entry.archived_at.format("%Y-%m-%d %H:%M"),
+        entry.reason.to_string(),
+        entry.task.priority.to_string(),
+        entry.task.task,
+    )
+}
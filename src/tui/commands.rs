@@ -0,0 +1,257 @@
+use ratatui::{
+    crossterm::event,
+    layout::{Constraint, Direction, Layout},
+    prelude::{Color, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{self, Block, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    commands::{
+        runner::{self, CommandDefinition, Run},
+        time_tracking,
+    },
+    structs::Data,
+    tui::{
+        self, events::TuiEvent, screen::Screen, AppState
+    }
+};
+
+
+// How many runs from earlier sessions are in the history
+const LOADED_RUNS: usize = 50;
+
+#[derive(Default, PartialEq)]
+enum Pane
+{
+    #[default]
+    Commands,
+    History,
+}
+
+#[derive(Default)]
+pub struct CommandsScreen
+{
+    // Oldest first, the history list shows them the other way around
+    runs: Vec<Run>,
+    commands_state: ListState,
+    history_state: ListState,
+    focus: Pane,
+    // None follows the end of the output
+    scroll: Option<u16>,
+    max_scroll: u16,
+    loaded: bool,
+}
+
+impl CommandsScreen
+{
+    fn shown_run(&self) -> Option<&Run>
+    {
+        let index: usize = self.history_state.selected()?;
+        self.runs.len().checked_sub(index + 1).map(|index| &self.runs[index])
+    }
+
+    fn start(&mut self, data: &Data, app_state: &mut AppState)
+    {
+        if self.runs.iter().any(Run::is_running)
+        {
+            app_state.set_error("Already running".to_string(), "Wait for the running command to finish, or stop it with x".to_string(), tui::ErrorType::Warning);
+            return;
+        }
+        let Some((name, definition)) = self.commands_state.selected()
+            .and_then(|index| data.commands.commands.iter().nth(index))
+        else
+        {
+            return;
+        };
+
+        match Run::start(name, definition)
+        {
+            Ok(run) =>
+            {
+                self.runs.push(run);
+                self.history_state.select(Some(0));
+                self.scroll = None;
+            }
+            Err(error) => app_state.set_error("Could not run command".to_string(), error, tui::ErrorType::Error),
+        }
+    }
+
+    fn scroll_by(&mut self, amount: i32)
+    {
+        let current: i32 = self.scroll.unwrap_or(self.max_scroll) as i32;
+        let next: i32 = (current + amount).max(0);
+        self.scroll = if next >= self.max_scroll as i32 { None } else { Some(next as u16) };
+    }
+}
+
+impl Screen for CommandsScreen
+{
+    fn title(&self) -> &'static str
+    {
+        "Commands"
+    }
+
+    fn owns(&self, state: &tui::TuiState) -> bool
+    {
+        matches!(state, tui::TuiState::Commands)
+    }
+
+    fn state(&self) -> tui::TuiState
+    {
+        tui::TuiState::Commands
+    }
+
+    // Output keeps coming in while on another tab, finished runs are saved right away
+    fn tick(&mut self, data: &mut Data, _app_state: &mut AppState)
+    {
+        for run in self.runs.iter_mut().filter(|run| run.is_running())
+        {
+            if run.poll()
+            {
+                data.runs.add(run.to_record());
+                runner::write_runs(&mut data.runs);
+            }
+        }
+    }
+
+    fn on_enter(&mut self, data: &mut Data, _app_state: &mut AppState)
+    {
+        if !self.loaded
+        {
+            let skipped: usize = data.runs.runs.len().saturating_sub(LOADED_RUNS);
+            self.runs = data.runs.runs[skipped..].iter().map(Run::from_record).collect();
+            self.loaded = true;
+        }
+        if self.commands_state.selected().is_none() && !data.commands.commands.is_empty()
+        {
+            self.commands_state.select_first();
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, data: &mut Data, _app_state: &AppState)
+    {
+        let colors = &data.settings.colors;
+        let chunks: [Rect; 2] = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Fill(1), Constraint::Length(1)])
+            .areas(area);
+        let [left, output_area]: [Rect; 2] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+            .areas(chunks[0]);
+        let [commands_area, history_area]: [Rect; 2] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Fill(1), Constraint::Fill(1)])
+            .areas(left);
+        let pane_color = |pane: Pane| if self.focus == pane { colors.selected } else { colors.default_text };
+
+        let command_items: Vec<ListItem> = data.commands.commands.iter()
+            .map(|(name, definition): (&String, &CommandDefinition)| ListItem::from(format!("{:<12} {}", name, definition.command_line())))
+            .collect();
+        let commands_title: &str = if command_items.is_empty() { "No commands in .dashboard/commands.toml" } else { "Commands" };
+        let commands = List::new(command_items)
+            .block(Block::bordered().title(commands_title).border_type(widgets::BorderType::Rounded).fg(pane_color(Pane::Commands)))
+            .fg(colors.default_text)
+            .highlight_symbol(">");
+        frame.render_stateful_widget(commands, commands_area, &mut self.commands_state);
+
+        let history_items: Vec<ListItem> = self.runs.iter()
+            .rev()
+            .map(|run|
+            {
+                let (result, color): (String, Color) = match (run.is_running(), run.exit_code)
+                {
+                    (true, _) => ("running".to_string(), colors.default_text),
+                    (false, Some(0)) => ("ok".to_string(), Color::Green),
+                    (false, Some(code)) => (format!("exit {}", code), Color::Red),
+                    (false, None) => ("killed".to_string(), Color::Red),
+                };
+                ListItem::from(format!(
+                    "{} {:<12} {:<8} {}",
+                    run.started.format("%H:%M:%S"), run.name, result, time_tracking::format_duration(run.duration())
+                )).fg(color)
+            })
+            .collect();
+        let history = List::new(history_items)
+            .block(Block::bordered().title("History").border_type(widgets::BorderType::Rounded).fg(pane_color(Pane::History)))
+            .highlight_symbol(">");
+        frame.render_stateful_widget(history, history_area, &mut self.history_state);
+
+        // The output of the run selected in the history, stderr in red
+        let (title, lines): (String, Vec<Line>) = match self.shown_run()
+        {
+            Some(run) =>
+            {
+                let status: String = match (run.is_running(), run.exit_code)
+                {
+                    (true, _) => "running".to_string(),
+                    (false, Some(code)) => format!("exit {}", code),
+                    (false, None) => "killed".to_string(),
+                };
+                let title: String = format!("{} ({}, {})", run.command_line, status, time_tracking::format_duration(run.duration()));
+                let lines: Vec<Line> = run.output.iter()
+                    .map(|line| if line.stderr { Line::from(line.text.clone()).fg(Color::Red) } else { Line::from(line.text.clone()) })
+                    .collect();
+                (title, lines)
+            }
+            None => ("Output".to_string(), Vec::new()),
+        };
+        self.max_scroll = u16::try_from(lines.len()).unwrap_or(u16::MAX).saturating_sub(output_area.height.saturating_sub(2));
+        let offset: u16 = self.scroll.unwrap_or(self.max_scroll).min(self.max_scroll);
+        let output = Paragraph::new(lines)
+            .scroll((offset, 0))
+            .block(Block::bordered().title(title).border_type(widgets::BorderType::Rounded))
+            .fg(colors.default_text);
+        frame.render_widget(output, output_area);
+
+        let help = Paragraph::new("Enter: Run | x: Stop | h/l: Commands/History | j/k: Move | Up/Down/PgUp/PgDn/End: Scroll | Esc: Overview")
+            .style(Style::default().fg(colors.default_text));
+        frame.render_widget(help, chunks[1]);
+    }
+
+    fn handle_event(&mut self, event: &TuiEvent, data: &mut Data, app_state: &mut AppState)
+    {
+        let TuiEvent::Key(key) = event else
+        {
+            return;
+        };
+        match key.code
+        {
+            event::KeyCode::Char('h') => self.focus = Pane::Commands,
+            event::KeyCode::Char('l') => self.focus = Pane::History,
+            event::KeyCode::Char('j') | event::KeyCode::Char('k') =>
+            {
+                let down: bool = key.code == event::KeyCode::Char('j');
+                let list_state: &mut ListState = match self.focus
+                {
+                    Pane::Commands => &mut self.commands_state,
+                    Pane::History =>
+                    {
+                        self.scroll = None;
+                        &mut self.history_state
+                    }
+                };
+                if down { list_state.select_next() } else { list_state.select_previous() }
+            }
+            event::KeyCode::Enter if self.focus == Pane::Commands => self.start(data, app_state),
+            event::KeyCode::Char('x') =>
+            {
+                for run in self.runs.iter_mut().filter(|run| run.is_running())
+                {
+                    run.kill();
+                }
+            }
+            event::KeyCode::Up => self.scroll_by(-1),
+            event::KeyCode::Down => self.scroll_by(1),
+            event::KeyCode::PageUp => self.scroll_by(-10),
+            event::KeyCode::PageDown => self.scroll_by(10),
+            event::KeyCode::End => self.scroll = None,
+            event::KeyCode::Esc => app_state.current_state = tui::TuiState::Overview,
+            _ => {},
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use chrono::Local;
+use ratatui::{
+    crossterm::event::{self, KeyEvent},
+    layout::{Constraint, Direction, Layout},
+    prelude::Rect,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{self, Block, Clear, List, ListItem, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    commands::{
+        self,
+        git::CommitLink,
+        time_tracking,
+    },
+    structs::{Data, Status},
+    tui::{
+        self, tasks::{format_due, format_tags, AddingState}, AppState, TasksState
+    }
+};
+
+
+pub fn handle_keys_detail(app_state: &mut AppState, key: KeyEvent, data: &mut Data, adding_state: &mut AddingState)
+{
+    match key.code
+    {
+        event::KeyCode::Esc | event::KeyCode::Char('q') =>
+        {
+            app_state.current_state = tui::TuiState::Tasks(TasksState::Main);
+        }
+        event::KeyCode::Char('E') =>
+        {
+            let Some(tasks) = data.tasks.as_ref() else
+            {
+                return;
+            };
+            if let Some(task) = tasks.list_state.selected().and_then(|index| tasks.tasks.get(index))
+            {
+                *adding_state = AddingState::from_task(task);
+                app_state.current_state = tui::TuiState::Tasks(TasksState::Editing);
+            }
+        }
+        _ => {},
+    }
+}
+
+// Asks about the first pending fix, y marks the task done and n leaves it as it is
+pub fn handle_keys_confirm_fix(app_state: &mut AppState, key: KeyEvent, data: &mut Data, pending_fixes: &mut Vec<(u64, String)>)
+{
+    let confirmed: bool = match key.code
+    {
+        event::KeyCode::Char('y') | event::KeyCode::Enter => true,
+        event::KeyCode::Char('n') | event::KeyCode::Esc => false,
+        _ => return,
+    };
+
+    if !pending_fixes.is_empty()
+    {
+        let (id, _) = pending_fixes.remove(0);
+        if let Some(tasks) = data.tasks.as_mut()
+            && let Some(index) = tasks.find(id)
+            && confirmed
+        {
+            tasks.snapshot();
+            tasks.tasks[index].status = Status::Done;
+            commands::archive::archive_finished(tasks, &mut data.archive);
+            commands::tasks::write_tasks(tasks);
+            commands::archive::write_archive(&mut data.archive);
+        }
+    }
+
+    if pending_fixes.is_empty()
+    {
+        app_state.current_state = tui::TuiState::Tasks(TasksState::Main);
+    }
+}
+
+pub fn render_detail(frame: &mut Frame, area: Rect, data: &mut Data, commit_links: &[CommitLink])
+{
+    let colors = &data.settings.colors;
+    let Some(task) = data.tasks.as_ref()
+        .and_then(|tasks| tasks.list_state.selected().and_then(|index| tasks.tasks.get(index)))
+    else
+    {
+        return;
+    };
+
+    let chunks: [Rect; 3] = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(12), Constraint::Fill(1), Constraint::Length(1)])
+        .areas(area);
+    let [description_area, links_area]: [Rect; 2] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .areas(chunks[1]);
+
+    let estimate: String = task.estimate.as_ref().map(|estimate| estimate.to_string()).unwrap_or("-".to_string());
+    let fields = Paragraph::new(vec![
+            Line::from(format!("Id:         #{}", task.id)),
+            Line::from(format!("Status:     {}", task.status)),
+            Line::from(format!("Priority:   {}", task.priority)),
+            Line::from(format!("Due:        {}", format_due(task))),
+            Line::from(format!("Tags:       {}", format_tags(task))),
+            Line::from(format!("Estimate:   {}", estimate)),
+            Line::from(format!("Tracked:    {}", time_tracking::format_duration(task.tracked_time(Local::now())))),
+            Line::from(format!("Pomodoros:  {}", task.pomodoros)),
+            Line::from(format!("Branches:   {}", task.branches.join(", "))),
+            Line::from(format!("Source:     {}", task.source.as_deref().unwrap_or_default())),
+        ])
+        .block(Block::bordered().title(task.task.clone()).border_type(widgets::BorderType::Rounded))
+        .fg(colors.default_text);
+    frame.render_widget(fields, chunks[0]);
+
+    let description = Paragraph::new(task.description.clone())
+        .wrap(Wrap { trim: false })
+        .block(Block::bordered().title("Description").border_type(widgets::BorderType::Rounded))
+        .fg(colors.default_text);
+    frame.render_widget(description, description_area);
+
+    // Commits that are no longer in the local history only have their hash
+    let commit_items: Vec<ListItem> = task.commits.iter()
+        .map(|hash|
+        {
+            let subject: &str = commit_links.iter()
+                .find(|link| link.hash == *hash)
+                .map_or("(not in the local history)", |link| link.subject.as_str());
+            ListItem::from(format!("{} {}", short_hash(hash), subject))
+        })
+        .collect();
+    let commits = List::new(commit_items)
+        .block(Block::bordered().title(format!("Commits ({})", task.commits.len())).border_type(widgets::BorderType::Rounded))
+        .fg(colors.default_text);
+    frame.render_widget(commits, links_area);
+
+    let help = Paragraph::new(format!("Mention #{} or [T-{}] in a commit to link it | E: Edit | Esc: Back", task.id, task.id))
+        .style(Style::default().fg(colors.default_text));
+    frame.render_widget(help, chunks[2]);
+}
+
+pub fn render_confirm_fix(frame: &mut Frame, area: Rect, data: &Data, pending_fixes: &[(u64, String)], commit_links: &[CommitLink])
+{
+    let Some((id, hash)) = pending_fixes.first() else
+    {
+        return;
+    };
+    let task: &str = data.tasks.as_ref()
+        .and_then(|tasks| tasks.find(*id).map(|index| tasks.tasks[index].task.as_str()))
+        .unwrap_or_default();
+    let subject: &str = commit_links.iter()
+        .find(|link| link.hash == *hash)
+        .map_or("", |link| link.subject.as_str());
+
+    let popup_area: Rect = tui::centered_rect(60, 30, area);
+    frame.render_widget(Clear, popup_area);
+    let message = Paragraph::new(format!(
+            "Commit {} \"{}\" fixes #{} \"{}\".\n\nMark the task as done?\n\ny: Yes | n: No",
+            short_hash(hash), subject, id, task
+        ))
+        .wrap(Wrap { trim: true })
+        .block(Block::bordered().title("Fixed by a commit").border_type(widgets::BorderType::Rounded))
+        .fg(data.settings.colors.default_text);
+    frame.render_widget(message, popup_area);
+}
+
+fn short_hash(hash: &str) -> &str
+{
+    &hash[..hash.len().min(7)]
+}
@@ -0,0 +1,115 @@
+use std::{
+    io,
+    sync::{atomic::{AtomicBool, Ordering}, Arc},
+    time::{Duration, Instant},
+};
+
+use signal_hook::consts::{SIGHUP, SIGTERM};
+
+use ratatui::crossterm::{
+    event::{self, DisableBracketedPaste, DisableFocusChange, EnableBracketedPaste, EnableFocusChange, Event, KeyEvent},
+    execute,
+};
+
+
+// Everything the render loops react to, terminal events and the tick timer
+#[derive(Debug, Clone)]
+pub enum TuiEvent
+{
+    Key(KeyEvent),
+    Paste(String),
+    Resize(u16, u16),
+    FocusGained,
+    FocusLost,
+    Tick,
+    // SIGTERM, SIGHUP, or the terminal went away
+    Terminate,
+}
+
+pub struct EventHandler
+{
+    tick_rate: Duration,
+    last_tick: Instant,
+    terminate: Arc<AtomicBool>,
+}
+
+impl Default for EventHandler
+{
+    fn default() -> Self
+    {
+        Self::new(Self::TICK_RATE)
+    }
+}
+
+impl EventHandler
+{
+    pub const TICK_RATE: Duration = Duration::from_millis(250);
+
+    pub fn new(tick_rate: Duration) -> Self
+    {
+        // The signal only sets the flag, the loop then saves and restores the terminal
+        let terminate: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        for signal in [SIGTERM, SIGHUP]
+        {
+            let _ = signal_hook::flag::register(signal, Arc::clone(&terminate));
+        }
+
+        Self
+        {
+            tick_rate,
+            last_tick: Instant::now(),
+            terminate,
+        }
+    }
+
+    // Blocks until a terminal event arrives or the next tick is due
+    pub fn next(&mut self) -> TuiEvent
+    {
+        loop
+        {
+            if self.terminate.load(Ordering::Relaxed)
+            {
+                return TuiEvent::Terminate;
+            }
+
+            let timeout: Duration = self.tick_rate.saturating_sub(self.last_tick.elapsed());
+            let Ok(ready) = event::poll(timeout) else
+            {
+                return TuiEvent::Terminate;
+            };
+            if ready
+            {
+                let Ok(event) = event::read() else
+                {
+                    return TuiEvent::Terminate;
+                };
+                match event
+                {
+                    Event::Key(key) => return TuiEvent::Key(key),
+                    Event::Paste(text) => return TuiEvent::Paste(text),
+                    Event::Resize(width, height) => return TuiEvent::Resize(width, height),
+                    Event::FocusGained => return TuiEvent::FocusGained,
+                    Event::FocusLost => return TuiEvent::FocusLost,
+                    Event::Mouse(_) => {},
+                }
+            }
+
+            if self.last_tick.elapsed() >= self.tick_rate
+            {
+                self.last_tick = Instant::now();
+                return TuiEvent::Tick;
+            }
+        }
+    }
+}
+
+// Paste and focus events are only sent by the terminal when asked for
+pub fn enable_extra_events()
+{
+    let _ = execute!(io::stdout(), EnableBracketedPaste, EnableFocusChange);
+}
+
+pub fn disable_extra_events()
+{
+    let _ = execute!(io::stdout(), DisableBracketedPaste, DisableFocusChange);
+}
@@ -0,0 +1,112 @@
+use std::{fs, path::PathBuf};
+
+use ratatui::{
+    crossterm::event::{self, KeyEvent},
+    prelude::Rect,
+    style::Stylize,
+    widgets::{self, Block, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    commands::{
+        self,
+        export::{self, ExportFormat, GroupBy},
+        git,
+        tasks::Task,
+    },
+    structs::Data,
+    tui::{self, AppState, TasksState},
+};
+
+
+// The marked tasks when there are any, otherwise the whole list, in the order it is shown
+fn export_targets(data: &Data) -> Vec<Task>
+{
+    let Some(tasks) = data.tasks.as_ref() else
+    {
+        return Vec::new();
+    };
+    if tasks.has_marks()
+    {
+        return (0..tasks.tasks.len())
+            .filter(|index| tasks.is_marked(*index))
+            .map(|index| tasks.tasks[index].clone())
+            .collect();
+    }
+    tasks.tasks.clone()
+}
+
+// Where an export in the format goes, next to .dashboard
+fn export_path(format: ExportFormat) -> PathBuf
+{
+    let mut path: PathBuf = git::project_dir();
+    path.push(format!("tasks-export.{}", format.extension()));
+    path
+}
+
+// Picks the format, and asks before an export replaces a file that is already there
+pub fn handle_keys_export(app_state: &mut AppState, key: KeyEvent, data: &mut Data, chosen: Option<ExportFormat>)
+{
+    let format: ExportFormat = match (chosen, key.code)
+    {
+        (Some(format), event::KeyCode::Char('y')) => format,
+        (None, event::KeyCode::Char('m')) => ExportFormat::Markdown,
+        (None, event::KeyCode::Char('c')) => ExportFormat::Csv,
+        (None, event::KeyCode::Char('j')) => ExportFormat::Json,
+        (None, event::KeyCode::Char('t')) => ExportFormat::Taskwarrior,
+        (Some(_), event::KeyCode::Char('n')) | (_, event::KeyCode::Esc) =>
+        {
+            app_state.current_state = tui::TuiState::Tasks(TasksState::Main);
+            return;
+        }
+        _ => return,
+    };
+    let path: PathBuf = export_path(format);
+    if chosen.is_none() && path.exists()
+    {
+        app_state.current_state = tui::TuiState::Tasks(TasksState::Export(Some(format)));
+        return;
+    }
+    app_state.current_state = tui::TuiState::Tasks(TasksState::Main);
+
+    let mut tasks: Vec<Task> = export_targets(data);
+    if format == ExportFormat::Taskwarrior
+        && let Some(all) = data.tasks.as_mut()
+        && export::ensure_uuids(&mut tasks, all)
+    {
+        commands::tasks::write_tasks(all);
+    }
+    match fs::write(&path, export::export(&tasks, format, GroupBy::Status))
+    {
+        Ok(()) => app_state.set_error(
+            "Exported".to_string(),
+            format!("Wrote {} tasks to {}", tasks.len(), path.display()),
+            tui::ErrorType::Info,
+        ),
+        Err(error) => app_state.set_error("Could not export".to_string(), error.to_string(), tui::ErrorType::Error),
+    }
+}
+
+pub fn render_export(frame: &mut Frame, area: Rect, data: &Data, chosen: Option<ExportFormat>)
+{
+    let text: String = match chosen
+    {
+        Some(format) => format!(
+            "{} already exists.\n\ny: Overwrite it | n: Cancel",
+            export_path(format).display()
+        ),
+        None => format!(
+            "Export {} tasks to {}/tasks-export.<format>\n\nm: Markdown | c: CSV | j: JSON | t: Taskwarrior | Esc: Cancel",
+            export_targets(data).len(),
+            git::project_dir().display()
+        ),
+    };
+    let popup_area: Rect = tui::centered_rect(50, 30, area);
+    frame.render_widget(Clear, popup_area);
+    let message = Paragraph::new(text)
+        .wrap(Wrap { trim: true })
+        .block(Block::bordered().title("Export").border_type(widgets::BorderType::Rounded))
+        .fg(data.settings.colors.default_text);
+    frame.render_widget(message, popup_area);
+}
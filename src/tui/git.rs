@@ -0,0 +1,175 @@
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    crossterm::event,
+    layout::{Constraint, Direction, Layout},
+    prelude::{Color, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{self, Block, List, ListItem, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    commands::git::{self, GitStatus},
+    structs::Data,
+    tui::{
+        self, events::TuiEvent, screen::Screen, AppState
+    }
+};
+
+
+#[derive(Default)]
+pub struct GitScreen
+{
+    status: Option<Result<GitStatus, String>>,
+    last_refresh: Option<Instant>,
+    active: bool,
+}
+
+impl GitScreen
+{
+    fn refresh(&mut self, data: &Data)
+    {
+        self.status = Some(git::read_status(&git::project_dir(), data.settings.git.commit_count));
+        self.last_refresh = Some(Instant::now());
+    }
+}
+
+impl Screen for GitScreen
+{
+    fn title(&self) -> &'static str
+    {
+        "Git"
+    }
+
+    fn owns(&self, state: &tui::TuiState) -> bool
+    {
+        matches!(state, tui::TuiState::Git)
+    }
+
+    fn state(&self) -> tui::TuiState
+    {
+        tui::TuiState::Git
+    }
+
+    fn on_enter(&mut self, data: &mut Data, _app_state: &mut AppState)
+    {
+        self.active = true;
+        self.refresh(data);
+    }
+
+    fn on_leave(&mut self, _data: &mut Data, _app_state: &mut AppState)
+    {
+        self.active = false;
+    }
+
+    // Only refreshes while shown, git can be slow on big repositories
+    fn tick(&mut self, data: &mut Data, _app_state: &mut AppState)
+    {
+        let interval: Duration = Duration::from_secs(data.settings.git.refresh_seconds.max(1));
+        if self.active && self.last_refresh.is_none_or(|last_refresh| last_refresh.elapsed() >= interval)
+        {
+            self.refresh(data);
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, data: &mut Data, _app_state: &AppState)
+    {
+        match &self.status
+        {
+            Some(Ok(status)) => render_git(frame, area, data, status),
+            Some(Err(error)) =>
+            {
+                let message = Paragraph::new(format!("Could not read the git repository:\n{}", error))
+                    .wrap(Wrap { trim: true })
+                    .block(Block::bordered().title("Git").border_type(widgets::BorderType::Rounded))
+                    .fg(data.settings.colors.default_text);
+                frame.render_widget(message, tui::centered_rect(60, 30, area));
+            }
+            None => {},
+        }
+    }
+
+    fn handle_event(&mut self, event: &TuiEvent, data: &mut Data, app_state: &mut AppState)
+    {
+        let TuiEvent::Key(key) = event else
+        {
+            return;
+        };
+        match key.code
+        {
+            event::KeyCode::Char('r') => self.refresh(data),
+            event::KeyCode::Esc => app_state.current_state = tui::TuiState::Overview,
+            _ => {},
+        }
+    }
+}
+
+fn render_git(frame: &mut Frame, area: Rect, data: &Data, status: &GitStatus)
+{
+    let colors = &data.settings.colors;
+
+    let chunks: [Rect; 3] = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(4), Constraint::Fill(1), Constraint::Length(1)])
+        .areas(area);
+    let [left, right]: [Rect; 2] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .areas(chunks[1]);
+    let [dirty_area, branches_area]: [Rect; 2] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Fill(2), Constraint::Fill(1)])
+        .areas(left);
+
+    // Branch, upstream and stashes
+    let upstream: String = match &status.upstream
+    {
+        Some(upstream) => format!("{}  ↑{} ↓{}", upstream, status.ahead, status.behind),
+        None => "no upstream".to_string(),
+    };
+    let summary = Paragraph::new(vec![
+            Line::from(format!("Branch:    {}", status.branch)),
+            Line::from(format!("Upstream:  {}", upstream)),
+        ])
+        .block(Block::bordered().title(format!("Stashes: {}", status.stashes)).border_type(widgets::BorderType::Rounded))
+        .fg(colors.default_text);
+    frame.render_widget(summary, chunks[0]);
+
+    // Changed files
+    let dirty_items: Vec<ListItem> = status.dirty.iter()
+        .map(|(code, path)| ListItem::from(format!("{} {}", code, path)))
+        .collect();
+    let dirty = List::new(dirty_items)
+        .block(Block::bordered().title(format!("Changes ({})", status.dirty.len())).border_type(widgets::BorderType::Rounded))
+        .fg(if status.dirty.is_empty() { colors.default_text } else { Color::Yellow });
+    frame.render_widget(dirty, dirty_area);
+
+    // Branches, the current one highlighted
+    let branch_items: Vec<ListItem> = status.branches.iter()
+        .map(|branch|
+        {
+            let item = ListItem::from(format!("{} {}", if branch.current { "*" } else { " " }, branch.name));
+            if branch.current { item.style(Style::default().fg(colors.selected)) } else { item }
+        })
+        .collect();
+    let branches = List::new(branch_items)
+        .block(Block::bordered().title("Branches").border_type(widgets::BorderType::Rounded))
+        .fg(colors.default_text);
+    frame.render_widget(branches, branches_area);
+
+    // Last commits
+    let commit_items: Vec<ListItem> = status.commits.iter()
+        .map(|commit| ListItem::from(format!("{} {:<16} {:<15} {}", commit.hash, commit.author, commit.date, commit.subject)))
+        .collect();
+    let commits = List::new(commit_items)
+        .block(Block::bordered().title("Commits").border_type(widgets::BorderType::Rounded))
+        .fg(colors.default_text);
+    frame.render_widget(commits, right);
+
+    let help = Paragraph::new("r: Refresh | Esc: Overview")
+        .style(Style::default().fg(colors.default_text));
+    frame.render_widget(help, chunks[2]);
+}
@@ -0,0 +1,221 @@
+use chrono::{Local, NaiveDate};
+use ratatui::{
+    crossterm::event::{self, KeyEvent},
+    layout::{Constraint, Direction, Layout},
+    prelude::Rect,
+    prelude::Color,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{self, Block, List, ListItem, Paragraph, Sparkline, SparklineBar, Wrap},
+    Frame,
+};
+
+use crate::{
+    commands::{
+        archive::ArchiveReason,
+        runner::RunRecord,
+        tasks::Task,
+        time_tracking,
+    },
+    structs::{Data, Priority, Status},
+    tui::{
+        self, events::TuiEvent, screen::Screen, AppState, TasksState
+    }
+};
+
+
+// How many recently completed tasks are shown
+const RECENT_COUNT: usize = 8;
+// How many commands and runs per command the trend shows
+const TREND_COMMANDS: usize = 6;
+const TREND_RUNS: usize = 50;
+
+pub struct OverviewScreen;
+
+impl Screen for OverviewScreen
+{
+    fn title(&self) -> &'static str
+    {
+        "Overview"
+    }
+
+    fn owns(&self, state: &tui::TuiState) -> bool
+    {
+        matches!(state, tui::TuiState::Overview)
+    }
+
+    fn state(&self) -> tui::TuiState
+    {
+        tui::TuiState::Overview
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, data: &mut Data, _app_state: &AppState)
+    {
+        render_overview(frame, area, data);
+    }
+
+    fn handle_event(&mut self, event: &TuiEvent, _data: &mut Data, app_state: &mut AppState)
+    {
+        if let TuiEvent::Key(key) = event
+        {
+            handle_keys_overview(app_state, *key);
+        }
+    }
+}
+
+fn handle_keys_overview(app_state: &mut AppState, key: KeyEvent)
+{
+    match key.code
+    {
+        event::KeyCode::Esc | event::KeyCode::Char('q') =>
+        {
+            app_state.current_state = tui::TuiState::Exit;
+        }
+        event::KeyCode::Enter | event::KeyCode::Char('t') =>
+        {
+            app_state.current_state = tui::TuiState::Tasks(TasksState::Main);
+        }
+        _ => {},
+    }
+}
+
+pub fn render_overview(frame: &mut Frame, area: Rect, data: &mut Data)
+{
+    let colors = &data.settings.colors;
+    let tasks: &[Task] = data.tasks.as_ref().map_or(&[], |tasks| tasks.tasks.as_slice());
+    let today: NaiveDate = Local::now().date_naive();
+
+    let trend_height: u16 = match data.runs.commands().len().min(TREND_COMMANDS)
+    {
+        0 => 0,
+        commands => commands as u16 + 2,
+    };
+    let chunks: [Rect; 4] = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(5), Constraint::Fill(1), Constraint::Length(trend_height), Constraint::Length(1)])
+        .areas(area);
+    let [counts_area, lists_area]: [Rect; 2] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .areas(chunks[1]);
+    let [overdue_area, recent_area]: [Rect; 2] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Fill(1), Constraint::Fill(1)])
+        .areas(lists_area);
+
+    // Project description
+    let name: String = data.overview.as_ref()
+        .and_then(|overview| overview.name.clone())
+        .unwrap_or_else(|| "Project".to_string());
+    let description: String = data.overview.as_ref()
+        .map(|overview| overview.description.clone())
+        .filter(|description| !description.is_empty())
+        .unwrap_or_else(|| "No description yet, add one in .dashboard/overview.toml".to_string());
+    let header = Paragraph::new(description)
+        .wrap(Wrap { trim: true })
+        .block(Block::bordered().title(name).border_type(widgets::BorderType::Rounded))
+        .fg(colors.default_text);
+    frame.render_widget(header, chunks[0]);
+
+    // Counts
+    let count_status = |status: Status| tasks.iter().filter(|task| task.status == status).count();
+    let count_priority = |priority: Priority| tasks.iter().filter(|task| task.priority == priority).count();
+    let completed: usize = data.archive.entries.iter().filter(|entry| entry.reason == ArchiveReason::Completed).count();
+    let lines: Vec<Line> = vec![
+        Line::from(format!("Todo:         {}", count_status(Status::Todo))),
+        Line::from(format!("In progress:  {}", count_status(Status::InProgress))),
+        Line::from(format!("Completed:    {}", completed)),
+        Line::from(""),
+        Line::from(format!("High:         {}", count_priority(Priority::High))),
+        Line::from(format!("Medium:       {}", count_priority(Priority::Medium))),
+        Line::from(format!("Low:          {}", count_priority(Priority::Low))),
+    ];
+    let counts = Paragraph::new(lines)
+        .block(Block::bordered().title("Tasks").border_type(widgets::BorderType::Rounded))
+        .fg(colors.default_text);
+    frame.render_widget(counts, counts_area);
+
+    // Overdue tasks, the oldest due date first
+    let mut overdue: Vec<&Task> = tasks.iter()
+        .filter(|task| task.due.is_some_and(|due| due < today))
+        .collect();
+    overdue.sort_by_key(|task| task.due);
+    let overdue_items: Vec<ListItem> = overdue.iter()
+        .map(|task| ListItem::from(format!("{}  {}", task.due.unwrap_or(today), task.task)))
+        .collect();
+    let overdue_list = List::new(overdue_items)
+        .block(Block::bordered().title(format!("Overdue ({})", overdue.len())).border_type(widgets::BorderType::Rounded))
+        .fg(if overdue.is_empty() { colors.default_text } else { ratatui::prelude::Color::Red });
+    frame.render_widget(overdue_list, overdue_area);
+
+    // Recently completed tasks, newest first
+    let recent_items: Vec<ListItem> = data.archive.entries.iter()
+        .rev()
+        .filter(|entry| entry.reason == ArchiveReason::Completed)
+        .take(RECENT_COUNT)
+        .map(|entry| ListItem::from(format!("{}  {}", entry.archived_at.format("%Y-%m-%d"), entry.task.task)))
+        .collect();
+    let recent_list = List::new(recent_items)
+        .block(Block::bordered().title("Recently completed").border_type(widgets::BorderType::Rounded))
+        .fg(colors.default_text);
+    frame.render_widget(recent_list, recent_area);
+
+    if trend_height > 0
+    {
+        render_run_trends(frame, chunks[2], data);
+    }
+
+    let help = Paragraph::new("Enter/t: Tasks | Esc/q: Quit")
+        .style(Style::default().fg(colors.default_text));
+    frame.render_widget(help, chunks[3]);
+}
+
+// A line per command: how often the last runs passed, and a bar per run that is as high as it was long, red when it failed
+fn render_run_trends(frame: &mut Frame, area: Rect, data: &Data)
+{
+    let colors = &data.settings.colors;
+    let block = Block::bordered()
+        .title("Command runs")
+        .border_type(widgets::BorderType::Rounded)
+        .fg(colors.default_text);
+    let inner: Rect = block.inner(area);
+    frame.render_widget(block, area);
+
+    let commands: Vec<&str> = data.runs.commands();
+    let rows: Vec<Rect> = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); commands.len().min(TREND_COMMANDS)])
+        .split(inner)
+        .to_vec();
+
+    for (command, row) in commands.iter().zip(rows)
+    {
+        let runs: Vec<&RunRecord> = data.runs.of_command(command).collect();
+        let runs: &[&RunRecord] = &runs[runs.len().saturating_sub(TREND_RUNS)..];
+        let passed: usize = runs.iter().filter(|run| run.succeeded()).count();
+        let last: &RunRecord = runs[runs.len() - 1];
+        let last_duration = chrono::TimeDelta::milliseconds((last.duration * 1000.0) as i64);
+
+        let [label_area, spark_area]: [Rect; 2] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(42), Constraint::Fill(1)])
+            .areas(row);
+        let label = Paragraph::new(format!(
+                "{:<12} {:>3}% of {:<3} last {}",
+                command, passed * 100 / runs.len(), runs.len(), time_tracking::format_duration(last_duration)
+            ))
+            .fg(if last.succeeded() { colors.default_text } else { Color::Red });
+        frame.render_widget(label, label_area);
+
+        // At least 1 so failed runs that stopped right away still show
+        let bars: Vec<SparklineBar> = runs.iter()
+            .map(|run|
+            {
+                let color: Color = if run.succeeded() { Color::Green } else { Color::Red };
+                SparklineBar::from(((run.duration * 1000.0) as u64).max(1)).style(Style::default().fg(color))
+            })
+            .collect();
+        frame.render_widget(Sparkline::default().data(bars), spark_area);
+    }
+}
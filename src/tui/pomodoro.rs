@@ -0,0 +1,297 @@
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local, TimeDelta};
+use ratatui::{
+    crossterm::{
+        event,
+        execute,
+        style::Print,
+    },
+    layout::{Constraint, Direction, Layout, Margin},
+    prelude::Rect,
+    style::{Modifier, Style},
+    widgets::{self, Block, Gauge, Paragraph},
+    Frame,
+};
+
+use crate::{
+    commands::{self, time_tracking::{self, TimeEntry}},
+    structs::{Data, PomodoroSettings},
+    tui::{
+        self, events::TuiEvent, screen::Screen, AppState, TasksState
+    }
+};
+
+
+// How long the screen flashes when a phase ends
+const FLASH_DURATION: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase
+{
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase
+{
+    fn title(&self) -> &'static str
+    {
+        match self
+        {
+            Phase::Work => "Work",
+            Phase::ShortBreak => "Short break",
+            Phase::LongBreak => "Long break",
+        }
+    }
+
+    fn length(&self, settings: &PomodoroSettings) -> Duration
+    {
+        let minutes: u32 = match self
+        {
+            Phase::Work => settings.work,
+            Phase::ShortBreak => settings.short_break,
+            Phase::LongBreak => settings.long_break,
+        };
+        Duration::from_secs(minutes.max(1) as u64 * 60)
+    }
+}
+
+pub struct PomodoroState
+{
+    pub task_id: u64,
+    pub phase: Phase,
+    // Time spent in the phase before the last pause
+    pub elapsed: Duration,
+    // None while paused
+    pub resumed_at: Option<Instant>,
+    pub completed: u32,
+    pub flash_until: Option<Instant>,
+}
+
+impl PomodoroState
+{
+    pub fn new(task_id: u64) -> Self
+    {
+        Self
+        {
+            task_id,
+            phase: Phase::Work,
+            elapsed: Duration::ZERO,
+            resumed_at: Some(Instant::now()),
+            completed: 0,
+            flash_until: None,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration
+    {
+        self.elapsed + self.resumed_at.map(|resumed_at| resumed_at.elapsed()).unwrap_or_default()
+    }
+
+    pub fn toggle_pause(&mut self)
+    {
+        match self.resumed_at.take()
+        {
+            Some(resumed_at) => self.elapsed += resumed_at.elapsed(),
+            None => self.resumed_at = Some(Instant::now()),
+        }
+    }
+
+    fn is_flashing(&self) -> bool
+    {
+        self.flash_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    // Moves on to the next phase, a long break after every few pomodoros
+    fn next_phase(&mut self, settings: &PomodoroSettings)
+    {
+        self.phase = match self.phase
+        {
+            Phase::Work if self.completed > 0 && self.completed.is_multiple_of(settings.long_break_every.max(1)) => Phase::LongBreak,
+            Phase::Work => Phase::ShortBreak,
+            Phase::ShortBreak | Phase::LongBreak => Phase::Work,
+        };
+        self.elapsed = Duration::ZERO;
+        self.resumed_at = Some(Instant::now());
+    }
+}
+
+// Keeps counting down while other tabs are shown
+#[derive(Default)]
+pub struct PomodoroScreen
+{
+    pomodoro: Option<PomodoroState>,
+}
+
+impl Screen for PomodoroScreen
+{
+    fn title(&self) -> &'static str
+    {
+        "Pomodoro"
+    }
+
+    fn owns(&self, state: &tui::TuiState) -> bool
+    {
+        matches!(state, tui::TuiState::Pomodoro(_))
+    }
+
+    fn state(&self) -> tui::TuiState
+    {
+        tui::TuiState::Pomodoro(self.pomodoro.as_ref().map_or(0, |pomodoro| pomodoro.task_id))
+    }
+
+    // Coming here for another task starts over for that task
+    fn on_enter(&mut self, _data: &mut Data, app_state: &mut AppState)
+    {
+        let tui::TuiState::Pomodoro(task_id) = app_state.current_state else
+        {
+            return;
+        };
+        if task_id != 0 && self.pomodoro.as_ref().is_none_or(|pomodoro| pomodoro.task_id != task_id)
+        {
+            self.pomodoro = Some(PomodoroState::new(task_id));
+        }
+    }
+
+    fn tick(&mut self, data: &mut Data, _app_state: &mut AppState)
+    {
+        if let Some(pomodoro) = self.pomodoro.as_mut()
+        {
+            update(data, pomodoro);
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, data: &mut Data, _app_state: &AppState)
+    {
+        match self.pomodoro.as_ref()
+        {
+            Some(pomodoro) => render_pomodoro(frame, area, data, pomodoro),
+            None =>
+            {
+                let message = Paragraph::new("No pomodoro running, start one with p on a task in the tasks tab")
+                    .centered()
+                    .style(Style::default().fg(data.settings.colors.default_text));
+                frame.render_widget(message, tui::centered_rect(60, 20, area));
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: &TuiEvent, data: &mut Data, app_state: &mut AppState)
+    {
+        let TuiEvent::Key(key) = event else
+        {
+            return;
+        };
+        match key.code
+        {
+            event::KeyCode::Esc =>
+            {
+                app_state.current_state = tui::TuiState::Tasks(TasksState::Main);
+            }
+            event::KeyCode::Char('x') =>
+            {
+                self.pomodoro = None;
+            }
+            event::KeyCode::Char(' ') =>
+            {
+                if let Some(pomodoro) = self.pomodoro.as_mut()
+                {
+                    pomodoro.toggle_pause();
+                }
+            }
+            event::KeyCode::Char('s') =>
+            {
+                if let Some(pomodoro) = self.pomodoro.as_mut()
+                {
+                    pomodoro.next_phase(&data.settings.pomodoro);
+                }
+            }
+            _ => {},
+        }
+    }
+}
+
+// Ends the phase when its time is up, logging finished work on the task
+fn update(data: &mut Data, pomodoro_state: &mut PomodoroState)
+{
+    if pomodoro_state.elapsed() < pomodoro_state.phase.length(&data.settings.pomodoro)
+    {
+        return;
+    }
+
+    if pomodoro_state.phase == Phase::Work
+    {
+        pomodoro_state.completed += 1;
+        if let Some(tasks) = data.tasks.as_mut()
+            && let Some(index) = tasks.find(pomodoro_state.task_id)
+        {
+            let task = &mut tasks.tasks[index];
+            task.pomodoros += 1;
+            // A running timer already counts this time
+            if !task.timer_running()
+            {
+                // Only the time it ran, so a pause in the middle is not logged as work
+                let now: DateTime<Local> = Local::now();
+                let worked: TimeDelta = TimeDelta::from_std(pomodoro_state.elapsed()).unwrap_or_default();
+                task.time_entries.push(TimeEntry { start: now - worked, end: Some(now) });
+            }
+            commands::tasks::write_tasks(tasks);
+        }
+    }
+
+    pomodoro_state.next_phase(&data.settings.pomodoro);
+    pomodoro_state.flash_until = Some(Instant::now() + FLASH_DURATION);
+    let _ = execute!(io::stdout(), Print("\x07"));
+}
+
+fn render_pomodoro(frame: &mut Frame, area: Rect, data: &mut Data, pomodoro_state: &PomodoroState)
+{
+    let task = data.tasks.as_ref()
+        .and_then(|tasks| tasks.find(pomodoro_state.task_id).map(|index| &tasks.tasks[index]));
+    let task_name: String = task.map(|task| task.task.clone()).unwrap_or_else(|| "Task no longer exists".to_string());
+    let task_pomodoros: u32 = task.map_or(0, |task| task.pomodoros);
+
+    let mut style = Style::default().fg(data.settings.colors.default_text);
+    if pomodoro_state.is_flashing()
+    {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+
+    let popup_area = tui::centered_rect(60, 40, area);
+    let mut title: String = format!(" {} - {} ", pomodoro_state.phase.title(), task_name);
+    if pomodoro_state.resumed_at.is_none()
+    {
+        title.push_str("(paused) ");
+    }
+    let block = Block::bordered()
+        .title(title)
+        .title_bottom(" Space: Pause | s: Skip | x: Stop | Esc: Back ")
+        .border_type(widgets::BorderType::Rounded)
+        .style(style);
+    frame.render_widget(block, popup_area);
+
+    let chunks: [Rect; 3] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Fill(1), Constraint::Length(3), Constraint::Fill(1)])
+        .areas(popup_area.inner(Margin::new(2, 1)));
+
+    let length: Duration = pomodoro_state.phase.length(&data.settings.pomodoro);
+    let elapsed: Duration = pomodoro_state.elapsed().min(length);
+    let remaining = chrono::TimeDelta::from_std(length - elapsed).unwrap_or_default();
+    let gauge = Gauge::default()
+        .block(Block::bordered().border_type(widgets::BorderType::Rounded))
+        .gauge_style(Style::default().fg(data.settings.colors.selected))
+        .ratio(elapsed.as_secs_f64() / length.as_secs_f64())
+        .label(format!("{} left", time_tracking::format_clock(remaining)));
+    frame.render_widget(gauge, chunks[1]);
+
+    let counts = Paragraph::new(format!("Pomodoros: {} this session, {} on this task", pomodoro_state.completed, task_pomodoros))
+        .centered()
+        .style(style);
+    frame.render_widget(counts, chunks[2]);
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeSet;
+
+use ratatui::{
+    crossterm::event::{self, KeyEvent},
+    prelude::Rect,
+    style::Stylize,
+    widgets::{self, Block, Clear, Paragraph, Wrap},
+    Frame,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    commands::{
+        self,
+        archive::{Archive, ArchivedTask},
+        merge,
+        tasks::{Task, Tasks},
+    },
+    files::{self, storage},
+    structs::Data,
+    tui::{self, AppState}
+};
+
+
+// The tasks changed on disk while there were changes that were not saved yet
+pub struct Conflict
+{
+    base: Vec<Task>,
+    theirs: Tasks,
+    theirs_content: String,
+    // The tasks both sides changed the same fields of, merging keeps mine for those
+    conflicts: Vec<merge::TaskConflict>,
+}
+
+// Picks up changes made outside the dashboard. Files without local changes are reloaded,
+// the archive is merged by entry, and tasks with changes on both sides ask the user
+pub fn apply_changes(changed: BTreeSet<String>, data: &mut Data, app_state: &mut AppState)
+{
+    for name in changed
+    {
+        match name.as_str()
+        {
+            "tasks" => reload_tasks(data, app_state),
+            "archive" => reload_archive(data),
+            "runs" =>
+            {
+                // Runs are written as soon as they finish, so there is nothing local to lose
+                if let Some((runs, content)) = read_changed("runs")
+                {
+                    data.runs = runs;
+                    files::set_synced("runs", content);
+                }
+            }
+            "settings" =>
+            {
+                if let Some((settings, _)) = read_changed("settings")
+                {
+                    data.settings = settings;
+                }
+            }
+            "overview" =>
+            {
+                if let Some((overview, _)) = read_changed("overview")
+                {
+                    data.overview = Some(overview);
+                }
+            }
+            "commands" =>
+            {
+                if let Some((commands, _)) = read_changed("commands")
+                {
+                    data.commands = commands;
+                }
+            }
+            _ => {},
+        }
+    }
+}
+
+// The file as it would be written now, or None when it can not be read or is what we last saw.
+// Half written files fail to parse, the next change event picks them up
+fn read_changed<T>(name: &str) -> Option<(T, String)>
+where
+    T: DeserializeOwned + Serialize,
+{
+    changed_value(name, files::read_toml(name)?)
+}
+
+fn changed_value<T>(name: &str, value: T) -> Option<(T, String)>
+where
+    T: Serialize,
+{
+    let content: String = toml::to_string_pretty(&value).ok()?;
+    if files::synced(name).is_some_and(|synced| synced == content)
+    {
+        return None;
+    }
+    Some((value, content))
+}
+
+fn reload_tasks(data: &mut Data, app_state: &mut AppState)
+{
+    let Some((theirs, theirs_content)) = storage::read_tasks().and_then(|tasks| changed_value("tasks", tasks)) else
+    {
+        return;
+    };
+    let synced: String = files::synced("tasks").unwrap_or_default();
+    let mine_content: String = data.tasks.as_ref()
+        .and_then(|tasks| toml::to_string_pretty(tasks).ok())
+        .unwrap_or_default();
+
+    if mine_content == theirs_content
+    {
+        files::set_synced("tasks", theirs_content);
+    } else if data.tasks.is_none() || mine_content == synced {
+        take_theirs(data, theirs, theirs_content);
+    } else {
+        let base: Vec<Task> = toml::from_str::<Tasks>(&synced).map(|tasks| tasks.tasks).unwrap_or_default();
+        let mine: &[Task] = data.tasks.as_ref().map(|tasks| tasks.tasks.as_slice()).unwrap_or_default();
+        let (_, conflicts) = merge::merge_tasks_with_conflicts(&base, mine, &theirs.tasks);
+        app_state.conflict = Some(Conflict { base, theirs, theirs_content, conflicts });
+    }
+}
+
+// The undo steps belong to the old tasks, so they go too
+fn take_theirs(data: &mut Data, mut theirs: Tasks, theirs_content: String)
+{
+    if let Some(mine) = data.tasks.as_ref()
+    {
+        theirs.list_state.select(mine.list_state.selected());
+    }
+    theirs.assign_ids();
+    theirs.clamp_selection();
+    files::set_synced("tasks", theirs_content.clone());
+
+    // Tasks without an id got one, which has to reach the file
+    if toml::to_string_pretty(&theirs).is_ok_and(|content| content != theirs_content)
+    {
+        commands::tasks::write_tasks(&mut theirs);
+    }
+    data.tasks = Some(theirs);
+}
+
+fn reload_archive(data: &mut Data)
+{
+    let Some((theirs, theirs_content)) = read_changed::<Archive>("archive") else
+    {
+        return;
+    };
+    let synced: String = files::synced("archive").unwrap_or_default();
+    let mine_content: String = toml::to_string_pretty(&data.archive).unwrap_or_default();
+    if mine_content == synced
+    {
+        data.archive.entries = theirs.entries;
+        files::set_synced("archive", theirs_content);
+        return;
+    }
+
+    let base: Vec<ArchivedTask> = toml::from_str::<Archive>(&synced).map(|archive| archive.entries).unwrap_or_default();
+    let mine: Vec<ArchivedTask> = std::mem::take(&mut data.archive.entries);
+    data.archive.entries = merge::merge_entries(&base, mine, theirs.entries, |entry| (entry.archived_at, entry.task.id));
+    data.archive.entries.sort_by_key(|entry| entry.archived_at);
+    commands::archive::write_archive(&mut data.archive);
+}
+
+pub fn handle_keys_conflict(key: KeyEvent, data: &mut Data, app_state: &mut AppState)
+{
+    let Some(conflict) = app_state.conflict.take() else
+    {
+        return;
+    };
+    let Some(mine) = data.tasks.as_mut() else
+    {
+        take_theirs(data, conflict.theirs, conflict.theirs_content);
+        return;
+    };
+
+    match key.code
+    {
+        // The merge can be undone like any other change
+        event::KeyCode::Char('m') =>
+        {
+            mine.snapshot();
+            merge::apply_merge(mine, &conflict.base, &conflict.theirs);
+            commands::tasks::write_tasks(mine);
+        }
+        // Mine goes to disk as it is, without merging in what is there
+        event::KeyCode::Char('k') =>
+        {
+            commands::tasks::save_tasks(mine);
+        }
+        event::KeyCode::Char('t') =>
+        {
+            take_theirs(data, conflict.theirs, conflict.theirs_content);
+        }
+        _ => app_state.conflict = Some(conflict),
+    }
+}
+
+pub fn render_conflict(frame: &mut Frame, conflict: &Conflict)
+{
+    let mut text: String = "The tasks were changed outside the dashboard while there were changes here that were not saved yet.\n\n".to_string();
+    if conflict.conflicts.is_empty()
+    {
+        text.push_str("No task has the same field changed on both sides.\n");
+    } else {
+        text.push_str("Changed on both sides, merging keeps mine:\n");
+        for task_conflict in &conflict.conflicts
+        {
+            text.push_str(&format!("#{}: {}\n", task_conflict.id, task_conflict.fields.join(", ")));
+        }
+    }
+    text.push_str("\nm: Merge both | k: Keep mine | t: Take theirs");
+
+    let popup_area: Rect = tui::centered_rect(60, 40, frame.area());
+    frame.render_widget(Clear, popup_area);
+    let message = Paragraph::new(text)
+        .wrap(Wrap { trim: true })
+        .block(Block::bordered().title("Tasks changed on disk").border_type(widgets::BorderType::Rounded))
+        .fg(ratatui::prelude::Color::Yellow);
+    frame.render_widget(message, popup_area);
+}
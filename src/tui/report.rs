@@ -0,0 +1,76 @@
+use ratatui::{
+    crossterm::event::{self, KeyEvent},
+    layout::{Constraint, Direction, Layout},
+    prelude::Rect,
+    style::{Modifier, Style, Stylize},
+    widgets::{self, Block, Paragraph, Row, Table},
+    Frame,
+};
+
+use crate::{
+    commands::estimates::{self, AccuracyRow},
+    structs::Data,
+    tui::{
+        self, tui::TuiColor, AppState, TasksState
+    }
+};
+
+
+pub fn handle_keys_report(app_state: &mut AppState, key: KeyEvent)
+{
+    if let event::KeyCode::Esc | event::KeyCode::Char('q') = key.code
+    {
+        app_state.current_state = tui::TuiState::Tasks(TasksState::Main);
+    }
+}
+
+pub fn render_report(frame: &mut Frame, area: Rect, data: &mut Data)
+{
+    let report = estimates::accuracy_report(&data.archive);
+
+    let chunks: [Rect; 3] = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Fill(1), Constraint::Fill(1), Constraint::Length(2)])
+        .areas(area);
+
+    frame.render_widget(accuracy_table("Estimates by tag", &report.by_tag, &data.settings.colors), chunks[0]);
+    frame.render_widget(accuracy_table("Estimates by priority", &report.by_priority, &data.settings.colors), chunks[1]);
+
+    let help = Paragraph::new("Completed tasks with an estimate. Ratio above 1 means it took longer than estimated.\nEsc: Back")
+        .style(Style::default().fg(data.settings.colors.default_text));
+    frame.render_widget(help, chunks[2]);
+}
+
+fn accuracy_table(title: &str, rows: &[AccuracyRow], colors: &TuiColor) -> Table<'static>
+{
+    let header = Row::new(["Group", "Tasks", "Estimated", "Tracked", "Ratio", "Points", "Days/pt"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = rows.iter()
+        .map(|row|
+        {
+            Row::new([
+                row.group.clone(),
+                row.tasks.to_string(),
+                format!("{:.1}h", row.estimated_hours),
+                format!("{:.1}h", row.tracked_hours),
+                row.hours_ratio().map(|ratio| format!("{:.2}", ratio)).unwrap_or_else(|| "-".to_string()),
+                row.points.to_string(),
+                row.days_per_point().map(|days| format!("{:.1}", days)).unwrap_or_else(|| "-".to_string()),
+            ])
+        })
+        .collect();
+
+    Table::new(rows, [
+        Constraint::Fill(2),
+        Constraint::Length(6),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(8),
+    ])
+    .header(header)
+    .block(Block::bordered().title(title.to_string()).border_type(widgets::BorderType::Rounded))
+    .fg(colors.default_text)
+}
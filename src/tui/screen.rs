@@ -0,0 +1,37 @@
+use ratatui::{prelude::Rect, Frame};
+
+use crate::{
+    structs::Data,
+    tui::{events::TuiEvent, AppState, TuiState},
+};
+
+
+// A top level screen of the dashboard, shown as a tab
+pub trait Screen
+{
+    fn title(&self) -> &'static str;
+
+    // Whether the current state of the app belongs to this screen
+    fn owns(&self, state: &TuiState) -> bool;
+
+    // The state to go to when switching to this screen with its tab
+    fn state(&self) -> TuiState;
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, data: &mut Data, app_state: &AppState);
+
+    // Only called for the active screen, key presses go through the error popup first
+    fn handle_event(&mut self, event: &TuiEvent, data: &mut Data, app_state: &mut AppState);
+
+    // Called for every screen on every tick, also when they are not shown
+    fn tick(&mut self, _data: &mut Data, _app_state: &mut AppState) {}
+
+    fn on_enter(&mut self, _data: &mut Data, _app_state: &mut AppState) {}
+
+    fn on_leave(&mut self, _data: &mut Data, _app_state: &mut AppState) {}
+
+    // While typing, number keys and Tab belong to the screen instead of switching tabs
+    fn captures_input(&self, _app_state: &AppState) -> bool
+    {
+        false
+    }
+}
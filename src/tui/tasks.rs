@@ -1,23 +1,28 @@
+use chrono::{DateTime, Local, NaiveDate};
 use ratatui::{
-    crossterm::event::{self, KeyEvent, KeyEventKind},
+    crossterm::event::{self, KeyEvent},
     layout::{Constraint, Direction, Layout, Margin},
     prelude::Rect,
     style::{Style, Stylize},
     widgets::{self, Block, Clear, List, ListItem, Paragraph, Widget, Wrap},
-    DefaultTerminal,
     Frame,
 };
 
 use crate::{
     commands::{
-        self
+        self,
+        estimates::Estimate,
+        git::{self, CommitLink},
+        recurrence::Recurrence,
+        time_tracking,
     },
     structs::{
         Data,
         Priority,
+        Status,
     },
     tui::{
-        self, tui::TuiColor, AppState, TasksState
+        self, archive::{render_archive, ArchiveViewState}, detail::{render_confirm_fix, render_detail}, report::render_report, events::TuiEvent, screen::Screen, tui::TuiColor, AppState, TasksState
     }
 };
 
@@ -26,14 +31,62 @@ pub enum AddingField
 {
     Task,
     Priority, 
+    Due,
+    Estimate,
+    Recurrence,
     Description,
 }
 
+// Actions that work on all marked tasks at once
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkAction
+{
+    Priority,
+    Status,
+    AddTag,
+    RemoveTag,
+    Project,
+}
+
+impl BulkAction
+{
+    fn title(&self) -> &'static str
+    {
+        match self
+        {
+            BulkAction::Priority => "Set priority",
+            BulkAction::Status => "Set status",
+            BulkAction::AddTag => "Add tag",
+            BulkAction::RemoveTag => "Remove tag",
+            BulkAction::Project => "Move to project",
+        }
+    }
+
+    fn help(&self) -> &'static str
+    {
+        match self
+        {
+            BulkAction::Priority => "h: High | m: Medium | l: Low | Esc: Cancel",
+            BulkAction::Status => "t: Todo | i: In progress | d: Done | c: Cancelled | Esc: Cancel",
+            BulkAction::AddTag | BulkAction::RemoveTag => "Enter: Apply | Esc: Cancel",
+            BulkAction::Project => "Enter: Apply (empty removes the project) | Esc: Cancel",
+        }
+    }
+
+    fn takes_text(&self) -> bool
+    {
+        matches!(self, BulkAction::AddTag | BulkAction::RemoveTag | BulkAction::Project)
+    }
+}
+
 pub struct AddingState
 {
     pub input_task: String,
     pub selected_priority: Priority,
     pub input_description: String,
+    pub input_due: String,
+    pub input_estimate: String,
+    pub input_recurrence: String,
     pub current_field: AddingField,
     pub description_scroll_offset: u16,
     pub form_dimensions: FormDimensions,
@@ -56,11 +109,28 @@ impl AddingState {
             input_task: String::new(),
             selected_priority: Priority::Medium,
             input_description: String::new(),
+            input_due: String::new(),
+            input_estimate: String::new(),
+            input_recurrence: String::new(),
             current_field: AddingField::Task,
             description_scroll_offset: 0,
             form_dimensions: FormDimensions::new(),
         }
     }
+
+    pub fn from_task(task: &commands::tasks::Task) -> Self
+    {
+        Self
+        {
+            input_task: task.task.clone(),
+            selected_priority: task.priority.clone(),
+            input_description: task.description.clone(),
+            input_due: task.due.map(|due| due.to_string()).unwrap_or_default(),
+            input_estimate: task.estimate.as_ref().map(|estimate| estimate.to_string()).unwrap_or_default(),
+            input_recurrence: task.recurrence.as_ref().map(|recurrence| recurrence.to_string()).unwrap_or_default(),
+            ..Self::new()
+        }
+    }
     
     // Auto-scroll to keep cursor visible
     pub fn calculate_max_scroll(&self, field_width: u16, field_height: u16) -> u16
@@ -74,7 +144,7 @@ impl AddingState {
         {
             1
         } else {
-            (self.input_description.len() as u16 + field_width - 1) / field_width
+            (self.input_description.len() as u16).div_ceil(field_width)
         };
         
         text_lines.saturating_sub(field_height)
@@ -150,7 +220,10 @@ impl AddingState {
         self.current_field = match self.current_field
         {
             AddingField::Task => AddingField::Priority,
-            AddingField::Priority => AddingField::Description,
+            AddingField::Priority => AddingField::Due,
+            AddingField::Due => AddingField::Estimate,
+            AddingField::Estimate => AddingField::Recurrence,
+            AddingField::Recurrence => AddingField::Description,
             AddingField::Description => AddingField::Task,
         };
     }
@@ -161,7 +234,10 @@ impl AddingState {
         {
             AddingField::Task => AddingField::Description,
             AddingField::Priority => AddingField::Task,
-            AddingField::Description => AddingField::Priority,
+            AddingField::Due => AddingField::Priority,
+            AddingField::Estimate => AddingField::Due,
+            AddingField::Recurrence => AddingField::Estimate,
+            AddingField::Description => AddingField::Recurrence,
         };
     }
     
@@ -180,10 +256,46 @@ impl AddingState {
                     _ => {}
                 }
             }
+            AddingField::Due => self.input_due.push(c),
+            AddingField::Estimate => self.input_estimate.push(c),
+            AddingField::Recurrence => self.input_recurrence.push(c),
             AddingField::Description => self.input_description.push(c),
         }
     }
     
+    // Pasted text goes into the active field, newlines only make sense in the description
+    pub fn handle_paste(&mut self, text: &str)
+    {
+        match self.current_field
+        {
+            AddingField::Task => self.input_task.push_str(&text.replace(['\r', '\n'], " ")),
+            AddingField::Priority =>
+            {
+                if let Some(c) = text.trim().chars().next()
+                {
+                    self.handle_character_input(c);
+                }
+            }
+            AddingField::Due => self.input_due.push_str(text.trim()),
+            AddingField::Estimate => self.input_estimate.push_str(text.trim()),
+            AddingField::Recurrence => self.input_recurrence.push_str(&text.replace(['\r', '\n'], " ")),
+            AddingField::Description =>
+            {
+                self.input_description.push_str(&text.replace("\r\n", "\n"));
+                let form_dimensions: FormDimensions = self.form_dimensions.clone();
+                self.auto_scroll_to_cursor(&form_dimensions);
+            }
+        }
+    }
+
+    // Recompute the description field size for a new terminal size and keep the scroll in bounds
+    pub fn resize(&mut self, frame_area: Rect)
+    {
+        self.form_dimensions = FormDimensions::calculate(form_layout(frame_area)[5]);
+        let form_dimensions: FormDimensions = self.form_dimensions.clone();
+        self.auto_scroll_to_cursor(&form_dimensions);
+    }
+
     fn handle_backspace(&mut self)
     {
         match self.current_field
@@ -198,6 +310,9 @@ impl AddingState {
                     Priority::Low => Priority::Medium,
                 };
             }
+            AddingField::Due => { self.input_due.pop(); }
+            AddingField::Estimate => { self.input_estimate.pop(); }
+            AddingField::Recurrence => { self.input_recurrence.pop(); }
             AddingField::Description => { self.input_description.pop(); }
         }
     }
@@ -229,17 +344,65 @@ impl AddingState {
     
     fn to_task(&self) -> commands::tasks::Task
     {
-        commands::tasks::Task
+        let mut task = commands::tasks::Task::new(self.input_task.clone(), self.selected_priority.clone(), self.input_description.clone());
+        self.apply_to(&mut task);
+        task
+    }
+
+    // Editing only touches the fields in the form, the rest of the task is kept
+    fn apply_to(&self, task: &mut commands::tasks::Task)
+    {
+        task.task = self.input_task.clone();
+        task.priority = self.selected_priority.clone();
+        task.description = self.input_description.clone();
+        task.due = self.parse_due().ok().flatten();
+        task.recurrence = self.parse_recurrence().ok().flatten();
+        task.estimate = self.parse_estimate().ok().flatten();
+    }
+
+    fn parse_due(&self) -> Result<Option<NaiveDate>, String>
+    {
+        let input: &str = self.input_due.trim();
+        if input.is_empty()
+        {
+            return Ok(None);
+        }
+        NaiveDate::parse_from_str(input, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| format!("'{}' is not a date, use YYYY-MM-DD", input))
+    }
+
+    fn parse_estimate(&self) -> Result<Option<Estimate>, String>
+    {
+        let input: &str = self.input_estimate.trim();
+        if input.is_empty()
+        {
+            return Ok(None);
+        }
+        input.parse().map(Some)
+    }
+
+    fn parse_recurrence(&self) -> Result<Option<Recurrence>, String>
+    {
+        let input: &str = self.input_recurrence.trim();
+        if input.is_empty()
         {
-            task: self.input_task.clone(),
-            priority: self.selected_priority.clone(),
-            description: self.input_description.clone(),
+            return Ok(None);
         }
+        input.parse().map(Some)
+    }
+
+    // The first problem with the input, if there is one
+    fn validation_error(&self) -> Option<String>
+    {
+        self.parse_due().err()
+            .or_else(|| self.parse_estimate().err())
+            .or_else(|| self.parse_recurrence().err())
     }
     
     fn is_valid(&self) -> bool
     {
-        !self.input_task.trim().is_empty()
+        !self.input_task.trim().is_empty() && self.validation_error().is_none()
     }
 }
 
@@ -269,94 +432,265 @@ impl FormDimensions
 
 
 
-pub fn run(terminal: &mut DefaultTerminal, data: &mut Data, app_state: &mut AppState) -> tui::TuiState
+pub struct TasksScreen
 {
-    let mut adding_state = AddingState::default();
+    adding_state: AddingState,
+    bulk_input: String,
+    archive_view: ArchiveViewState,
+    // Where to come back to when switching back to this tab
+    last_state: TasksState,
+    commit_links: Vec<CommitLink>,
+    // The branch heads of the last scan, the commits before them are in commit_links already
+    scanned_heads: Vec<String>,
+    // Task id and commit hash of the fixes still waiting to be confirmed
+    pending_fixes: Vec<(u64, String)>,
+}
 
-    'tasks_render_loop: loop
+impl TasksScreen
+{
+    pub fn new() -> Self
     {
-        let tui::TuiState::Tasks(ref task_state) = app_state.current_state else
-        {
-            break 'tasks_render_loop;
-        };
-
-        if matches!(task_state, TasksState::Exit)
+        Self
         {
-            break 'tasks_render_loop;
+            adding_state: AddingState::default(),
+            bulk_input: String::new(),
+            archive_view: ArchiveViewState::default(),
+            last_state: TasksState::Main,
+            commit_links: Vec::new(),
+            scanned_heads: Vec::new(),
+            pending_fixes: Vec::new(),
         }
+    }
 
-        // rendering
-        match task_state
+    // Links commits and branches that mention task ids, from the local repository
+    fn scan_git(&mut self, data: &mut Data) -> Result<(), String>
+    {
+        let repo = git::project_dir();
+        let heads: Vec<String> = git::branch_heads(&repo)?;
+        let commits: Vec<CommitLink> = if heads == self.scanned_heads
         {
-            TasksState::Main =>
-            {
-                draw_terminal!(terminal => render_main(data): app_state, data);
-            }
-            TasksState::Adding =>
+            Vec::new()
+        } else {
+            // A head from before that is gone, like after a gc, means reading everything again
+            git::scan_commit_links(&repo, &heads, &self.scanned_heads).or_else(|_|
             {
-                draw_terminal!(terminal => render_adding(data, &mut adding_state): app_state, data);
-            }
-            TasksState::Editing =>
+                self.commit_links.clear();
+                git::scan_commit_links(&repo, &heads, &[])
+            })?
+        };
+        let branches = git::scan_branch_links(&repo)?;
+        if let Some(tasks) = data.tasks.as_mut()
+        {
+            let (linked, fixes) = tasks.link_git(&commits, &branches);
+            if linked
             {
-                draw_terminal!(terminal => render_editing(data, &mut adding_state): app_state, data);
+                commands::tasks::write_tasks(tasks);
             }
-            TasksState::Exit => unreachable!(),
+            self.pending_fixes.extend(fixes);
         }
+        self.commit_links.extend(commits);
+        self.scanned_heads = heads;
+        Ok(())
+    }
 
-        // input handling
-        let event::Event::Key(key) = event::read().unwrap() else
+    fn confirm_fixes(&self, app_state: &mut AppState)
+    {
+        if !self.pending_fixes.is_empty()
         {
-            continue 'tasks_render_loop;
-        };
+            app_state.current_state = tui::TuiState::Tasks(TasksState::ConfirmFix);
+        }
+    }
 
-        if key.kind != KeyEventKind::Press
+    fn handle_paste(&mut self, task_state: &TasksState, text: &str)
+    {
+        match task_state
         {
-            continue 'tasks_render_loop;
+            TasksState::Adding | TasksState::Editing => self.adding_state.handle_paste(text),
+            TasksState::Bulk(action) if action.takes_text() => self.bulk_input.push_str(text.trim()),
+            TasksState::Archive if self.archive_view.searching => self.archive_view.search.push_str(text.trim()),
+            _ => {},
         }
+    }
 
-        if app_state.has_error()
+    fn handle_key(&mut self, task_state: &TasksState, key: KeyEvent, data: &mut Data, app_state: &mut AppState)
+    {
+        match task_state
         {
-            match key.code
+            TasksState::Main if key.code == event::KeyCode::Enter =>
             {
-                event::KeyCode::Enter | event::KeyCode::Esc | event::KeyCode::Char(' ') =>
+                if data.tasks.as_ref().and_then(|tasks| tasks.list_state.selected()).is_some()
                 {
-                    app_state.clear_error();
+                    // Not being in a git repository only means there is nothing to link
+                    let _ = self.scan_git(data);
+                    app_state.current_state = tui::TuiState::Tasks(TasksState::Detail);
+                }
+            }
+            TasksState::Main if key.code == event::KeyCode::Char('G') =>
+            {
+                match self.scan_git(data)
+                {
+                    Ok(()) => self.confirm_fixes(app_state),
+                    Err(error) => app_state.set_error("Could not read git".to_string(), error, tui::ErrorType::Error),
                 }
-                _ => {}
             }
-            continue 'tasks_render_loop;
-        }
-
-        match task_state
-        {
             TasksState::Main =>
             {
-                handle_keys_main(app_state, key, data, &mut adding_state);
+                handle_keys_main(app_state, key, data, &mut self.adding_state);
+                self.bulk_input.clear();
             }
             TasksState::Adding =>
             {
-                handle_keys_adding(app_state, key, data, &mut adding_state);
+                handle_keys_adding(app_state, key, data, &mut self.adding_state);
             }
             TasksState::Editing =>
             {
                 let selected_index = data.tasks.as_ref().unwrap().list_state.selected().unwrap();
-                handle_keys_editing(app_state, key, data, &mut adding_state, selected_index);
+                handle_keys_editing(app_state, key, data, &mut self.adding_state, selected_index);
+            }
+            TasksState::Bulk(action) =>
+            {
+                handle_keys_bulk(app_state, key, data, &mut self.bulk_input, action.clone());
+            }
+            TasksState::Archive =>
+            {
+                tui::archive::handle_keys_archive(app_state, key, data, &mut self.archive_view);
+            }
+            TasksState::Report =>
+            {
+                tui::report::handle_keys_report(app_state, key);
+            }
+            TasksState::Detail =>
+            {
+                tui::detail::handle_keys_detail(app_state, key, data, &mut self.adding_state);
+            }
+            TasksState::ConfirmFix =>
+            {
+                tui::detail::handle_keys_confirm_fix(app_state, key, data, &mut self.pending_fixes);
+            }
+            TasksState::Export(format) =>
+            {
+                tui::export::handle_keys_export(app_state, key, data, *format);
+            }
+        }
+    }
+}
+
+impl Screen for TasksScreen
+{
+    fn title(&self) -> &'static str
+    {
+        "Tasks"
+    }
+
+    fn owns(&self, state: &tui::TuiState) -> bool
+    {
+        matches!(state, tui::TuiState::Tasks(_))
+    }
+
+    fn state(&self) -> tui::TuiState
+    {
+        tui::TuiState::Tasks(self.last_state.clone())
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, data: &mut Data, app_state: &AppState)
+    {
+        let tui::TuiState::Tasks(task_state) = &app_state.current_state else
+        {
+            return;
+        };
+
+        match task_state
+        {
+            TasksState::Main => render_main(frame, area, data),
+            TasksState::Adding => render_adding(frame, area, data, &mut self.adding_state),
+            TasksState::Editing => render_editing(frame, area, data, &mut self.adding_state),
+            TasksState::Bulk(action) => render_bulk(frame, area, data, action, &self.bulk_input),
+            TasksState::Archive => render_archive(frame, area, data, &self.archive_view),
+            TasksState::Report => render_report(frame, area, data),
+            TasksState::Detail => render_detail(frame, area, data, &self.commit_links),
+            TasksState::ConfirmFix =>
+            {
+                render_main(frame, area, data);
+                render_confirm_fix(frame, area, data, &self.pending_fixes, &self.commit_links);
             }
-            TasksState::Exit =>
+            TasksState::Export(format) =>
             {
-                break 'tasks_render_loop;
+                render_main(frame, area, data);
+                tui::export::render_export(frame, area, data, *format);
             }
         }
     }
-    commands::tasks::write_tasks(data.tasks.as_ref().unwrap());
-    tui::TuiState::Exit
+
+    fn handle_event(&mut self, event: &TuiEvent, data: &mut Data, app_state: &mut AppState)
+    {
+        let tui::TuiState::Tasks(task_state) = &app_state.current_state else
+        {
+            return;
+        };
+        let task_state: TasksState = task_state.clone();
+
+        match event
+        {
+            TuiEvent::Key(key) => self.handle_key(&task_state, *key, data, app_state),
+            TuiEvent::Paste(text) => self.handle_paste(&task_state, text),
+            TuiEvent::Resize(width, height) =>
+            {
+                self.adding_state.resize(tui::screen_area(Rect::new(0, 0, *width, *height)));
+            }
+            _ => {},
+        }
+
+        if let tui::TuiState::Tasks(task_state) = &app_state.current_state
+        {
+            self.last_state = task_state.clone();
+        }
+    }
+
+    // Picks up commits made since the tab was last open
+    fn on_enter(&mut self, data: &mut Data, app_state: &mut AppState)
+    {
+        if self.scan_git(data).is_ok() && matches!(app_state.current_state, tui::TuiState::Tasks(TasksState::Main))
+        {
+            self.confirm_fixes(app_state);
+        }
+    }
+
+    fn on_leave(&mut self, data: &mut Data, _app_state: &mut AppState)
+    {
+        if let Some(tasks) = data.tasks.as_mut()
+        {
+            commands::tasks::write_tasks(tasks);
+        }
+        commands::archive::write_archive(&mut data.archive);
+    }
+
+    fn captures_input(&self, app_state: &AppState) -> bool
+    {
+        match &app_state.current_state
+        {
+            tui::TuiState::Tasks(TasksState::Adding | TasksState::Editing | TasksState::Bulk(_) | TasksState::ConfirmFix | TasksState::Export(_)) => true,
+            tui::TuiState::Tasks(TasksState::Archive) => self.archive_view.searching,
+            _ => false,
+        }
+    }
 }
 
+#[allow(clippy::needless_return)]
 fn handle_keys_main(app_state: &mut AppState, key: KeyEvent, data: &mut Data, adding_state: &mut AddingState)
 {
     match key.code
     {
-        event::KeyCode::Esc => { app_state.current_state = tui::TuiState::Tasks(TasksState::Exit); return; }
+        event::KeyCode::Esc =>
+        {
+            // Esc first drops the marks, and only goes back to the overview when nothing is marked
+            if let Some(tasks) = data.tasks.as_mut().filter(|tasks| tasks.has_marks())
+            {
+                tasks.clear_marks();
+                return;
+            }
+            app_state.current_state = tui::TuiState::Overview;
+            return;
+        }
         event::KeyCode::Char(char) =>
         {
             if let Some(tasks) = data.tasks.as_mut()
@@ -373,14 +707,7 @@ fn handle_keys_main(app_state: &mut AppState, key: KeyEvent, data: &mut Data, ad
                     { 
                         if let Some(index) = tasks.list_state.selected()
                         {
-                            *adding_state = AddingState {
-                                current_field: AddingField::Task,
-                                input_task: tasks.tasks[index].task.clone(),
-                                selected_priority: tasks.tasks[index].priority.clone(),
-                                input_description: tasks.tasks[index].description.clone(),
-                                description_scroll_offset: 0,
-                                form_dimensions: FormDimensions::new(),
-                            };
+                            *adding_state = AddingState::from_task(&tasks.tasks[index]);
                             app_state.current_state = tui::TuiState::Tasks(tui::TasksState::Editing);
                             return;
                         } else {
@@ -389,11 +716,91 @@ fn handle_keys_main(app_state: &mut AppState, key: KeyEvent, data: &mut Data, ad
                         };
                     },
                     'X' =>
+                    {
+                        let deleted = tasks.bulk_delete();
+                        commands::archive::archive_deleted(tasks, &mut data.archive, deleted);
+                    }
+                    'R' =>
+                    {
+                        app_state.current_state = tui::TuiState::Tasks(TasksState::Report);
+                        return;
+                    }
+                    'x' =>
+                    {
+                        app_state.current_state = tui::TuiState::Tasks(TasksState::Export(None));
+                        return;
+                    }
+                    'a' =>
+                    {
+                        data.archive.list_state.select_first();
+                        app_state.current_state = tui::TuiState::Tasks(TasksState::Archive);
+                        return;
+                    }
+                    ' ' =>
                     {
                         if let Some(index) = tasks.list_state.selected()
                         {
-                            tasks.tasks.remove(index);
+                            tasks.toggle_mark(index);
+                        }
+                    }
+                    'V' =>
+                    {
+                        tasks.toggle_range();
+                    }
+                    'P' | 'S' | '+' | '-' | 'M' =>
+                    {
+                        if tasks.targets().is_empty()
+                        {
+                            app_state.set_error("Nothing selected".to_string(), "No task has been selected or marked".to_string(), tui::ErrorType::Warning);
+                            return;
+                        }
+                        let action: BulkAction = match char
+                        {
+                            'P' => BulkAction::Priority,
+                            'S' => BulkAction::Status,
+                            '+' => BulkAction::AddTag,
+                            '-' => BulkAction::RemoveTag,
+                            _ => BulkAction::Project,
                         };
+                        app_state.current_state = tui::TuiState::Tasks(TasksState::Bulk(action));
+                        return;
+                    }
+                    'u' =>
+                    {
+                        match tasks.undo()
+                        {
+                            Some(archived) => data.archive.remove_entries(&archived),
+                            None => app_state.set_error("Nothing to undo".to_string(), "There are no changes left to undo".to_string(), tui::ErrorType::Info),
+                        }
+                    }
+                    'p' =>
+                    {
+                        if let Some(index) = tasks.list_state.selected().filter(|index| *index < tasks.tasks.len())
+                        {
+                            app_state.current_state = tui::TuiState::Pomodoro(tasks.tasks[index].id);
+                        } else {
+                            app_state.set_error("Nothing selected".to_string(), "No task has been selected".to_string(), tui::ErrorType::Warning);
+                        }
+                        return;
+                    }
+                    // Side effects stay out of the match guards
+                    #[allow(clippy::collapsible_match)]
+                    't' =>
+                    {
+                        // Saved right away so a running timer survives a crash
+                        if tasks.toggle_timer()
+                        {
+                            commands::tasks::write_tasks(tasks);
+                        }
+                    }
+                    #[allow(clippy::collapsible_match)]
+                    'K' | 'J' =>
+                    {
+                        // The order of the file is the order of the list, so save it right away
+                        if tasks.move_selected(char == 'K')
+                        {
+                            commands::tasks::write_tasks(tasks);
+                        }
                     }
                     'k' =>
                     {
@@ -435,17 +842,20 @@ fn handle_keys_form(
             {
                 if let Some(tasks) = data.tasks.as_mut()
                 {
-                    let new_task = adding_state.to_task();
-                    
+                    tasks.snapshot();
                     match index
                     {
-                        Some(idx) => tasks.tasks[idx] = new_task, // Edit mode
-                        None => tasks.tasks.push(new_task),       // Add mode
+                        Some(idx) => adding_state.apply_to(&mut tasks.tasks[idx]), // Edit mode
+                        None => tasks.add(adding_state.to_task()),                 // Add mode
                     }
                 }
                 app_state.current_state = tui::TuiState::Tasks(TasksState::Main);
                 return;
             }
+            if let Some(message) = adding_state.validation_error()
+            {
+                app_state.set_error("Invalid task".to_string(), message, tui::ErrorType::Warning);
+            }
         }
         
         event::KeyCode::Char(c) =>
@@ -491,19 +901,118 @@ fn handle_keys_editing(app_state: &mut AppState, key: KeyEvent, data: &mut Data,
     handle_keys_form(app_state, key, data, adding_state, Some(index))
 }
 
+fn handle_keys_bulk(app_state: &mut AppState, key: KeyEvent, data: &mut Data, input: &mut String, action: BulkAction)
+{
+    let Some(tasks) = data.tasks.as_mut() else
+    {
+        app_state.current_state = tui::TuiState::Tasks(TasksState::Main);
+        return;
+    };
+
+    match key.code
+    {
+        event::KeyCode::Esc =>
+        {
+            input.clear();
+            app_state.current_state = tui::TuiState::Tasks(TasksState::Main);
+            return;
+        }
+        event::KeyCode::Backspace if action.takes_text() =>
+        {
+            input.pop();
+            return;
+        }
+        event::KeyCode::Char(c) if action.takes_text() =>
+        {
+            // Tags and projects are single words
+            if !c.is_whitespace()
+            {
+                input.push(c);
+            }
+            return;
+        }
+        event::KeyCode::Enter if action.takes_text() =>
+        {
+            let value: String = input.trim().to_string();
+            match action
+            {
+                BulkAction::AddTag if !value.is_empty() => { tasks.bulk_update(|task| task.add_tag(&value)); }
+                BulkAction::RemoveTag if !value.is_empty() => { tasks.bulk_update(|task| task.remove_tag(&value)); }
+                BulkAction::Project =>
+                {
+                    let project: Option<String> = Some(value).filter(|value| !value.is_empty());
+                    tasks.bulk_update(|task| task.project = project.clone());
+                }
+                _ => return,
+            }
+        }
+        event::KeyCode::Char(c) if action == BulkAction::Priority =>
+        {
+            let priority: Priority = match c.to_ascii_lowercase()
+            {
+                'h' => Priority::High,
+                'm' => Priority::Medium,
+                'l' => Priority::Low,
+                _ => return,
+            };
+            tasks.bulk_update(|task| task.priority = priority.clone());
+        }
+        event::KeyCode::Char(c) if action == BulkAction::Status =>
+        {
+            let Some(status) = Status::from_char(c) else
+            {
+                return;
+            };
+            tasks.bulk_update(|task| task.status = status.clone());
+            commands::archive::archive_finished(tasks, &mut data.archive);
+        }
+        _ => return,
+    }
+
+    input.clear();
+    app_state.current_state = tui::TuiState::Tasks(TasksState::Main);
+}
+
+fn form_popup_area(frame_area: Rect) -> Rect
+{
+    tui::centered_rect(70, 60, frame_area)
+}
+
+// The areas of the form fields, shared between rendering and resize handling
+// In order: task, priority, due, estimate, recurrence, description and help
+fn form_layout(frame_area: Rect) -> [Rect; 7]
+{
+    let inner_area = form_popup_area(frame_area).inner(Margin::new(1, 1));
+    let rows: [Rect; 4] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),      // Task name - fixed
+            Constraint::Length(3),      // Priority, due, estimate and recurrence - fixed  
+            Constraint::Min(5),         // Description - grows as needed
+            Constraint::Length(2),      // Help text - fixed
+        ])
+        .areas(inner_area);
+    let [priority, due, estimate, recurrence]: [Rect; 4] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(20), Constraint::Percentage(25), Constraint::Percentage(20), Constraint::Percentage(35)])
+        .areas(rows[1]);
+    [rows[0], priority, due, estimate, recurrence, rows[2], rows[3]]
+}
+
 fn render_form(
     frame: &mut Frame, 
+    area: Rect,
     data: &mut Data, 
     adding_state: &mut AddingState, 
     title: &str,
     help_text: &str
 )
 {
-    render_main(frame, data);
+    render_main(frame, area, data);
     
     
     // Make popup bigger to accommodate more content
-    let popup_area = tui::centered_rect(70, 60, frame.area());
+    let popup_area = form_popup_area(area);
     frame.render_widget(Clear, popup_area);
     
     let popup_block = Block::bordered()
@@ -513,23 +1022,17 @@ fn render_form(
     
     frame.render_widget(popup_block, popup_area);
     
-    let inner_area = popup_area.inner(Margin::new(1, 1));
-    let chunks: [Rect; 4] = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3),      // Task name - fixed
-            Constraint::Length(3),      // Priority - fixed  
-            Constraint::Min(5),         // Description - grows as needed
-            Constraint::Length(2),      // Help text - fixed
-        ])
-        .areas(inner_area);
+    let chunks: [Rect; 7] = form_layout(area);
     
-    adding_state.form_dimensions = FormDimensions::calculate(chunks[2]);
+    adding_state.form_dimensions = FormDimensions::calculate(chunks[5]);
 
     // Render all fields as paragraphs
     let field_data = [
         ("Task Name", &adding_state.input_task, adding_state.current_field == AddingField::Task, false),
         ("Priority (h/m/l or ↑↓)", &adding_state.selected_priority.to_string(), adding_state.current_field == AddingField::Priority, false),
+        ("Due (YYYY-MM-DD)", &adding_state.input_due, adding_state.current_field == AddingField::Due, false),
+        ("Estimate", &adding_state.input_estimate, adding_state.current_field == AddingField::Estimate, false),
+        ("Repeat", &adding_state.input_recurrence, adding_state.current_field == AddingField::Recurrence, false),
         ("Description", &adding_state.input_description, adding_state.current_field == AddingField::Description, true),
     ];
     
@@ -552,7 +1055,7 @@ fn render_form(
         };
         
         // Add scrolling for description field
-        if i == 2 && *wrap {
+        if *wrap {
             paragraph = paragraph.scroll((adding_state.description_scroll_offset, 0));
         }
         
@@ -562,24 +1065,30 @@ fn render_form(
     // Help text with scroll instructions
     let help_with_scroll = match adding_state.current_field
     {
-        AddingField::Task => format!("{}", help_text),
+        AddingField::Task => help_text.to_string(),
         AddingField::Priority => format!("{} | h/m/l or ↑↓", help_text),
+        AddingField::Due => format!("{} | e.g. 2025-01-31, empty for none", help_text),
+        AddingField::Estimate => format!("{} | hours like 2.5h or points like 3pt", help_text),
+        AddingField::Recurrence => format!("{} | daily, weekly mon fri, monthly or every 3 days", help_text),
         AddingField::Description => format!("{} | ↑↓ to scroll", help_text),
     };
     
     let help = Paragraph::new(help_with_scroll)
         .style(Style::default().fg(data.settings.colors.default_text));
-    frame.render_widget(help, chunks[3]);
+    frame.render_widget(help, chunks[6]);
     
     // Set cursor position with wrapping consideration for description
     let cursor_pos = match adding_state.current_field
     {
         AddingField::Task => (chunks[0].x + adding_state.input_task.len() as u16 + 1, chunks[0].y + 1),
         AddingField::Priority => (chunks[1].x + 1, chunks[1].y + 1),
+        AddingField::Due => (chunks[2].x + adding_state.input_due.len() as u16 + 1, chunks[2].y + 1),
+        AddingField::Estimate => (chunks[3].x + adding_state.input_estimate.len() as u16 + 1, chunks[3].y + 1),
+        AddingField::Recurrence => (chunks[4].x + adding_state.input_recurrence.len() as u16 + 1, chunks[4].y + 1),
         AddingField::Description => {
             // Calculate wrapped position considering scroll
-            let field_width = chunks[2].width.saturating_sub(2);
-            let field_height = chunks[2].height.saturating_sub(2);
+            let field_width = chunks[5].width.saturating_sub(2);
+            let field_height = chunks[5].height.saturating_sub(2);
             let text_len = adding_state.input_description.len() as u16;
             let line = text_len / field_width;
             let col = text_len % field_width;
@@ -589,12 +1098,12 @@ fn render_form(
             
             // Keep cursor within visible area
             let cursor_y = if visible_line < field_height {
-                chunks[2].y + visible_line + 1
+                chunks[5].y + visible_line + 1
             } else {
-                chunks[2].y + field_height // Bottom of visible area
+                chunks[5].y + field_height // Bottom of visible area
             };
             
-            (chunks[2].x + col + 1, cursor_y)
+            (chunks[5].x + col + 1, cursor_y)
         },
     };
     frame.set_cursor_position(cursor_pos);
@@ -602,55 +1111,137 @@ fn render_form(
     adding_state.auto_scroll_to_cursor(&form_dimensions);
 }
 
-fn render_adding(frame: &mut Frame, data: &mut Data, adding_state: &mut AddingState)
+fn render_adding(frame: &mut Frame, area: Rect, data: &mut Data, adding_state: &mut AddingState)
 {
-    render_form(frame, data, adding_state, "Add New Task", 
+    render_form(frame, area, data, adding_state, "Add New Task", 
                 "Tab: Next field | Enter: Add task | Esc: Cancel");
 }
 
-fn render_editing(frame: &mut Frame, data: &mut Data, adding_state: &mut AddingState)
+fn render_editing(frame: &mut Frame, area: Rect, data: &mut Data, adding_state: &mut AddingState)
 {
-    render_form(frame, data, adding_state, "Edit Task", 
+    render_form(frame, area, data, adding_state, "Edit Task", 
                 "Tab: Next field | Enter: Save task | Esc: Cancel");
 }
 
-fn create_task_list<'a, F>(tasks: &'a [commands::tasks::Task], extractor: F, colors: &TuiColor) -> List<'a>
+fn render_bulk(frame: &mut Frame, area: Rect, data: &mut Data, action: &BulkAction, input: &str)
+{
+    render_main(frame, area, data);
+
+    let count: usize = data.tasks.as_ref().map_or(0, |tasks| tasks.targets().len());
+    let popup_area = tui::centered_rect(50, 20, area);
+    frame.render_widget(Clear, popup_area);
+
+    let popup_block = Block::bordered()
+        .title(format!("{} ({} tasks)", action.title(), count))
+        .border_type(widgets::BorderType::Rounded)
+        .fg(data.settings.colors.default_text);
+    frame.render_widget(popup_block, popup_area);
+
+    let chunks: [Rect; 2] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Fill(1)])
+        .areas(popup_area.inner(Margin::new(1, 1)));
+
+    if action.takes_text()
+    {
+        let field = Paragraph::new(input)
+            .block(Block::bordered().title(if *action == BulkAction::Project { "Project" } else { "Tag" }))
+            .style(Style::default().fg(data.settings.colors.selected));
+        frame.render_widget(field, chunks[0]);
+        frame.set_cursor_position((chunks[0].x + input.len() as u16 + 1, chunks[0].y + 1));
+    }
+
+    let help = Paragraph::new(action.help())
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(data.settings.colors.default_text));
+    frame.render_widget(help, chunks[1]);
+}
+
+fn create_task_list<F>(tasks: &commands::tasks::Tasks, extractor: F, colors: &TuiColor) -> List<'static>
 where 
-    F: Fn(&commands::tasks::Task) -> String,
+    F: Fn(usize, &commands::tasks::Task) -> String,
 {
     List::new(
-        tasks.iter()
-            .map(|task| ListItem::from(extractor(task)))
+        tasks.tasks.iter()
+            .enumerate()
+            .map(|(index, task)|
+            {
+                let item = ListItem::from(extractor(index, task));
+                if tasks.is_marked(index)
+                {
+                    item.style(Style::default().fg(colors.marked))
+                } else {
+                    item
+                }
+            })
             .collect::<Vec<_>>()
     )
     .highlight_style(Style::default().fg(colors.selected))
 }
 
-fn render_main(frame: &mut Frame, data: &mut Data)
+// The due date, with a mark for recurring tasks
+pub fn format_due(task: &commands::tasks::Task) -> String
+{
+    let due: String = task.due.map(|due| due.to_string()).unwrap_or_default();
+    match task.recurrence
+    {
+        Some(_) => format!("{} ↻", due).trim_start().to_string(),
+        None => due,
+    }
+}
+
+pub fn format_tags(task: &commands::tasks::Task) -> String
+{
+    task.project.iter()
+        .map(|project| format!("+{}", project))
+        .chain(task.tags.iter().map(|tag| format!("#{}", tag)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn render_main(frame: &mut Frame, area: Rect, data: &mut Data)
 {
-    let chunks: [Rect; 1] = Layout::default()
+    let chunks: [Rect; 2] = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
-        .constraints([Constraint::Fill(1)])
-        .areas(frame.area());
+        .constraints([Constraint::Fill(1), Constraint::Length(1)])
+        .areas(area);
 
-    let chunks_inner: [Rect; 3] = Layout::default()
+    let chunks_inner: [Rect; 7] = Layout::default()
         .direction(Direction::Horizontal)
         .margin(1)
-        .constraints([Constraint::Percentage(30), Constraint::Percentage(20), Constraint::Percentage(50)])
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Percentage(25),
+            Constraint::Percentage(12),
+            Constraint::Percentage(10),
+            Constraint::Length(13),
+            Constraint::Percentage(15),
+            Constraint::Fill(1),
+        ])
         .areas(chunks[0]);
 
-    Block::bordered()
+    let marked_count: usize = data.tasks.as_ref()
+        .map_or(0, |tasks| (0..tasks.tasks.len()).filter(|index| tasks.is_marked(*index)).count());
+    let mut block = Block::bordered()
         .border_type(widgets::BorderType::Rounded)
-        .fg(data.settings.colors.default_text)
-        .render(chunks[0], frame.buffer_mut());
+        .fg(data.settings.colors.default_text);
+    if marked_count > 0
+    {
+        block = block.title_bottom(format!(" {} marked | X: Delete | x: Export | P: Priority | S: Status | +/-: Tag | M: Project | Esc: Unmark ", marked_count));
+    }
+    block.render(chunks[0], frame.buffer_mut());
 
     if let Some(tasks_data) = data.tasks.as_mut()
     {
         let lists = [
-            (create_task_list(&tasks_data.tasks, |t| t.task.clone(), &data.settings.colors).highlight_symbol(">"), chunks_inner[0]),
-            (create_task_list(&tasks_data.tasks, |t| t.priority.to_string(), &data.settings.colors), chunks_inner[1]),
-            (create_task_list(&tasks_data.tasks, |t| t.description.clone(), &data.settings.colors), chunks_inner[2]),
+            (create_task_list(tasks_data, |i, _| if tasks_data.is_marked(i) { "*".to_string() } else { String::new() }, &data.settings.colors), chunks_inner[0]),
+            (create_task_list(tasks_data, |_, t| t.task.clone(), &data.settings.colors).highlight_symbol(">"), chunks_inner[1]),
+            (create_task_list(tasks_data, |_, t| t.status.to_string(), &data.settings.colors), chunks_inner[2]),
+            (create_task_list(tasks_data, |_, t| t.priority.to_string(), &data.settings.colors), chunks_inner[3]),
+            (create_task_list(tasks_data, |_, t| format_due(t), &data.settings.colors), chunks_inner[4]),
+            (create_task_list(tasks_data, |_, t| format_tags(t), &data.settings.colors), chunks_inner[5]),
+            (create_task_list(tasks_data, |_, t| t.description.clone(), &data.settings.colors), chunks_inner[6]),
         ];
 
         for (list, area) in lists
@@ -658,4 +1249,41 @@ fn render_main(frame: &mut Frame, data: &mut Data)
             frame.render_stateful_widget(list, area, &mut tasks_data.list_state);
         }
     }
+
+    render_status_bar(frame, data, chunks[1]);
+}
+
+// The running timer and the time tracked on the selected task, redrawn every tick
+fn render_status_bar(frame: &mut Frame, data: &Data, area: Rect)
+{
+    let Some(tasks) = data.tasks.as_ref() else
+    {
+        return;
+    };
+    let now: DateTime<Local> = Local::now();
+
+    let timer: String = match tasks.running_timer()
+    {
+        Some(task) =>
+        {
+            let current = task.time_entries.last().map(|entry| entry.duration(now)).unwrap_or_default();
+            format!(" ⏱ {} {} ", task.task, time_tracking::format_clock(current))
+        }
+        None => " No timer running (t: Start) ".to_string(),
+    };
+    let selected: String = tasks.list_state.selected()
+        .and_then(|index| tasks.tasks.get(index))
+        .map(|task|
+        {
+            let estimate: String = task.estimate.as_ref().map(|estimate| format!(" of {}", estimate)).unwrap_or_default();
+            format!("Tracked on selected: {}{} ", time_tracking::format_duration(task.tracked_time(now)), estimate)
+        })
+        .unwrap_or_default();
+
+    let [timer_area, selected_area]: [Rect; 2] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Fill(1), Constraint::Length(selected.chars().count() as u16)])
+        .areas(area);
+    frame.render_widget(Paragraph::new(timer).style(Style::default().fg(data.settings.colors.selected)), timer_area);
+    frame.render_widget(Paragraph::new(selected).style(Style::default().fg(data.settings.colors.default_text)), selected_area);
 }
@@ -0,0 +1,142 @@
+use ratatui::{
+    crossterm::event,
+    layout::{Constraint, Direction, Layout},
+    prelude::Rect,
+    style::{Style, Stylize},
+    widgets::{self, Block, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    commands::{
+        self,
+        git,
+        tasks::Task,
+        todos::{self, CodeTodo},
+    },
+    structs::{Data, Priority},
+    tui::{
+        self, events::TuiEvent, screen::Screen, AppState
+    }
+};
+
+
+#[derive(Default)]
+pub struct CodeTodosScreen
+{
+    todos: Vec<CodeTodo>,
+    list_state: ListState,
+}
+
+impl CodeTodosScreen
+{
+    fn scan(&mut self, data: &Data)
+    {
+        self.todos = todos::scan(&git::project_dir(), &data.settings.todos.markers);
+        if self.list_state.selected().is_none_or(|index| index >= self.todos.len())
+        {
+            self.list_state.select((!self.todos.is_empty()).then_some(0));
+        }
+    }
+
+    // Turns the selected marker into a task that remembers where it came from
+    fn promote(&mut self, data: &mut Data, app_state: &mut AppState)
+    {
+        let Some(todo) = self.list_state.selected().and_then(|index| self.todos.get(index)) else
+        {
+            return;
+        };
+        let Some(tasks) = data.tasks.as_mut() else
+        {
+            return;
+        };
+        if tasks.tasks.iter().any(|task| task.source.as_deref() == Some(todo.location().as_str()))
+        {
+            app_state.set_error("Already a task".to_string(), format!("{} is already on the task list", todo.location()), tui::ErrorType::Info);
+            return;
+        }
+
+        let title: String = if todo.text.is_empty() { todo.context.clone() } else { todo.text.clone() };
+        let mut task: Task = Task::new(title, Priority::Medium, todo.context.clone());
+        task.source = Some(todo.location());
+        task.add_tag(&todo.marker.to_lowercase());
+
+        tasks.snapshot();
+        tasks.add(task);
+        commands::tasks::write_tasks(tasks);
+        app_state.set_error("Task added".to_string(), format!("{} is now a task", todo.location()), tui::ErrorType::Info);
+    }
+}
+
+impl Screen for CodeTodosScreen
+{
+    fn title(&self) -> &'static str
+    {
+        "Code TODOs"
+    }
+
+    fn owns(&self, state: &tui::TuiState) -> bool
+    {
+        matches!(state, tui::TuiState::CodeTodos)
+    }
+
+    fn state(&self) -> tui::TuiState
+    {
+        tui::TuiState::CodeTodos
+    }
+
+    fn on_enter(&mut self, data: &mut Data, _app_state: &mut AppState)
+    {
+        self.scan(data);
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, data: &mut Data, _app_state: &AppState)
+    {
+        let colors = &data.settings.colors;
+        let chunks: [Rect; 2] = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Fill(1), Constraint::Length(1)])
+            .areas(area);
+
+        // Markers that are already tasks get a check mark
+        let items: Vec<ListItem> = self.todos.iter()
+            .map(|todo|
+            {
+                let promoted: bool = data.tasks.as_ref()
+                    .is_some_and(|tasks| tasks.tasks.iter().any(|task| task.source.as_deref() == Some(todo.location().as_str())));
+                ListItem::from(format!(
+                    "{} {:<32} {:<6} {}",
+                    if promoted { "✓" } else { " " }, todo.location(), todo.marker, todo.context
+                ))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!("Code TODOs ({})", self.todos.len())).border_type(widgets::BorderType::Rounded))
+            .fg(colors.default_text)
+            .highlight_style(Style::default().fg(colors.selected))
+            .highlight_symbol(">");
+        frame.render_stateful_widget(list, chunks[0], &mut self.list_state);
+
+        let help = Paragraph::new("Enter: Make a task | r: Rescan | j/k: Move | Esc: Overview")
+            .style(Style::default().fg(colors.default_text));
+        frame.render_widget(help, chunks[1]);
+    }
+
+    fn handle_event(&mut self, event: &TuiEvent, data: &mut Data, app_state: &mut AppState)
+    {
+        let TuiEvent::Key(key) = event else
+        {
+            return;
+        };
+        match key.code
+        {
+            event::KeyCode::Char('j') | event::KeyCode::Down => self.list_state.select_next(),
+            event::KeyCode::Char('k') | event::KeyCode::Up => self.list_state.select_previous(),
+            event::KeyCode::Enter => self.promote(data, app_state),
+            event::KeyCode::Char('r') => self.scan(data),
+            event::KeyCode::Esc => app_state.current_state = tui::TuiState::Overview,
+            _ => {},
+        }
+    }
+}
@@ -1,18 +1,32 @@
+use std::time::{Duration, Instant};
+
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    crossterm::event::{self, KeyEventKind},
+    layout::{Alignment, Constraint, Direction, Layout},
     prelude::Rect, 
-    style::Color, 
+    style::{Color, Style, Stylize},
+    widgets::{Paragraph, Tabs},
     DefaultTerminal, 
     Frame,
 };
 
 use crate::{
     commands::{
-        self
+        self,
+        export::ExportFormat,
     },
+    files::{self, DashboardWatcher},
     structs::Data,
     tui::{
-        tasks,
+        commands::CommandsScreen,
+        events::{self, EventHandler, TuiEvent},
+        git::GitScreen,
+        overview::OverviewScreen,
+        pomodoro::PomodoroScreen,
+        reload::{self, Conflict},
+        screen::Screen,
+        tasks::{self, TasksScreen},
+        todos::CodeTodosScreen,
     }
 };
 
@@ -22,18 +36,29 @@ pub struct TuiColor
 {
     pub default_text: Color,
     pub selected: Color,
+
+    #[serde(default = "marked_default")]
+    pub marked: Color,
 }
 
+fn marked_default() -> Color { TuiColor::MARKED }
+
 impl TuiColor
 {
     pub const DEFAULT_TEXT: Color = Color::Blue;
     pub const SELECTED: Color = Color::Gray;
+    pub const MARKED: Color = Color::Yellow;
 }
 
 pub struct AppState
 {
     pub current_state: TuiState,
     pub error_state: Option<ErrorInfo>,
+    pub events: EventHandler,
+    pub focused: bool,
+    pub conflict: Option<Conflict>,
+    // Changes that are not in the files yet
+    pub unsaved: bool,
 }
 
 
@@ -43,8 +68,12 @@ impl AppState
     {
         Self
         {
-            current_state: TuiState::Tasks(TasksState::Main),
+            current_state: TuiState::Overview,
             error_state: None,
+            events: EventHandler::default(),
+            focused: true,
+            conflict: None,
+            unsaved: false,
         }
     }
     
@@ -83,36 +112,249 @@ pub enum ErrorType
 
 pub enum TuiState
 {
+    Overview,
     Tasks(TasksState),
+    // The id of the task the pomodoros are for
+    Pomodoro(u64),
+    Git,
+    CodeTodos,
+    Commands,
     Exit,
 }
 
+#[derive(Clone)]
 pub enum TasksState
 {
     Main,
     Adding,
     Editing,
-    Exit,
+    Bulk(tasks::BulkAction),
+    Archive,
+    Report,
+    Detail,
+    // Asks whether tasks that a commit fixes should be marked done
+    ConfirmFix,
+    // Picks the format for exporting the marked tasks, or all of them.
+    // With a format the file is already there and it asks before overwriting it
+    Export(Option<ExportFormat>),
 }
 
+// How long changes wait before they are saved, so a burst of them is one write
+const SAVE_DELAY: Duration = Duration::from_secs(1);
+
 pub fn start()
 {
     color_eyre::install().unwrap();
+    events::enable_extra_events();
 }
 
+pub fn stop()
+{
+    events::disable_extra_events();
+}
+
+// Routes events to the screen of the active tab, and switches tabs when the state moves to another screen
 pub fn run(mut terminal: DefaultTerminal, data: &mut Data)
 {
     let mut app_state: AppState = AppState::new();
+    if files::shared()
+    {
+        let holder: String = files::lock_holder().map(|pid| format!(" (process {})", pid)).unwrap_or_default();
+        app_state.set_error(
+            "Another dashboard is open".to_string(),
+            format!("Another dashboard{} has this project open. This one saves every change by merging it with the files on disk first.", holder),
+            ErrorType::Info,
+        );
+    }
+    let mut screens: Vec<Box<dyn Screen>> = vec![
+        Box::new(OverviewScreen),
+        Box::new(TasksScreen::new()),
+        Box::new(PomodoroScreen::default()),
+        Box::new(GitScreen::default()),
+        Box::new(CodeTodosScreen::default()),
+        Box::new(CommandsScreen::default()),
+    ];
+    let mut active: usize = screen_index(&screens, &app_state.current_state);
+    screens[active].on_enter(data, &mut app_state);
+    // Without a watcher the dashboard still works, it just does not see outside changes
+    let watcher: Option<DashboardWatcher> = DashboardWatcher::new().ok();
+    let mut unsaved_since: Option<Instant> = None;
+    // Set when the data may have changed, only then is it compared with the files
+    let mut data_changed: bool = true;
+
     'main_render_loop: loop
     {
-        app_state.current_state = match app_state.current_state
+        // rendering
+        draw_terminal!(terminal => render_screens(&mut screens, active, data, &app_state): app_state, data);
+
+        // input handling
+        let tui_event: TuiEvent = app_state.events.next();
+        match &tui_event
         {
-            TuiState::Tasks(_) => tasks::run(&mut terminal, data, &mut app_state),
+            TuiEvent::Tick =>
+            {
+                if let Some(watcher) = watcher.as_ref()
+                {
+                    let changed = watcher.changed_files();
+                    if !changed.is_empty()
+                    {
+                        reload::apply_changes(changed, data, &mut app_state);
+                        data_changed = true;
+                    }
+                }
+                for screen in screens.iter_mut()
+                {
+                    screen.tick(data, &mut app_state);
+                }
+            }
+            TuiEvent::FocusGained =>
+            {
+                app_state.focused = true;
+                terminal.clear().unwrap();
+            }
+            TuiEvent::FocusLost =>
+            {
+                app_state.focused = false;
+            }
+            TuiEvent::Terminate =>
+            {
+                app_state.current_state = TuiState::Exit;
+            }
+            TuiEvent::Key(key) if key.kind != KeyEventKind::Press => {},
+            TuiEvent::Key(key) if app_state.has_error() =>
+            {
+                if let event::KeyCode::Enter | event::KeyCode::Esc | event::KeyCode::Char(' ') = key.code
+                {
+                    app_state.clear_error();
+                }
+            }
+            TuiEvent::Key(key) if app_state.conflict.is_some() =>
+            {
+                reload::handle_keys_conflict(*key, data, &mut app_state);
+            }
+            TuiEvent::Key(key) if !screens[active].captures_input(&app_state) =>
+            {
+                let target: Option<usize> = match key.code
+                {
+                    event::KeyCode::Tab => Some((active + 1) % screens.len()),
+                    event::KeyCode::BackTab => Some((active + screens.len() - 1) % screens.len()),
+                    event::KeyCode::Char(c @ '1'..='9') => Some(c as usize - '1' as usize).filter(|index| *index < screens.len()),
+                    _ => None,
+                };
+                match target
+                {
+                    Some(index) => app_state.current_state = screens[index].state(),
+                    None => screens[active].handle_event(&tui_event, data, &mut app_state),
+                }
+            }
+            TuiEvent::Key(_) | TuiEvent::Paste(_) | TuiEvent::Resize(_, _) =>
+            {
+                screens[active].handle_event(&tui_event, data, &mut app_state);
+            }
+        }
 
-            TuiState::Exit => break 'main_render_loop,
+        // Ticks only save what they change, keys and pastes are what edit the data
+        if !matches!(tui_event, TuiEvent::Tick | TuiEvent::FocusGained | TuiEvent::FocusLost)
+        {
+            data_changed = true;
+        }
+        // Waits with saving while a conflict is open, saving would pick "keep mine"
+        if data_changed
+        {
+            app_state.unsaved = has_unsaved_changes(data);
+            data_changed = false;
+        }
+        match unsaved_since
+        {
+            _ if !app_state.unsaved || app_state.conflict.is_some() => unsaved_since = None,
+            None => unsaved_since = Some(Instant::now()),
+            Some(since) if since.elapsed() >= SAVE_DELAY =>
+            {
+                save(data);
+                app_state.unsaved = false;
+                unsaved_since = None;
+            }
+            Some(_) => {},
+        }
+
+        if matches!(app_state.current_state, TuiState::Exit)
+        {
+            screens[active].on_leave(data, &mut app_state);
+            break 'main_render_loop;
+        }
+
+        let next: usize = screen_index(&screens, &app_state.current_state);
+        if next != active
+        {
+            screens[active].on_leave(data, &mut app_state);
+            active = next;
+            screens[active].on_enter(data, &mut app_state);
+            data_changed = true;
         }
     }
-    commands::tasks::write_tasks(data.tasks.as_ref().unwrap());
+    save(data);
+}
+
+fn has_unsaved_changes(data: &Data) -> bool
+{
+    data.tasks.as_ref().is_some_and(|tasks| files::is_unsaved("tasks", tasks))
+        || files::is_unsaved("archive", &data.archive)
+}
+
+fn save(data: &mut Data)
+{
+    if let Some(tasks) = data.tasks.as_mut()
+    {
+        commands::tasks::write_tasks(tasks);
+    }
+    commands::archive::write_archive(&mut data.archive);
+}
+
+fn screen_index(screens: &[Box<dyn Screen>], state: &TuiState) -> usize
+{
+    screens.iter().position(|screen| screen.owns(state)).unwrap_or(0)
+}
+
+// The area below the tab bar that the screens draw in
+pub fn screen_area(frame_area: Rect) -> Rect
+{
+    let [_, screen_area]: [Rect; 2] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Fill(1)])
+        .areas(frame_area);
+    screen_area
+}
+
+fn render_screens(frame: &mut Frame, screens: &mut [Box<dyn Screen>], active: usize, data: &mut Data, app_state: &AppState)
+{
+    let titles: Vec<String> = screens.iter()
+        .enumerate()
+        .map(|(index, screen)| format!("{} {}", index + 1, screen.title()))
+        .collect();
+    let tabs = Tabs::new(titles)
+        .select(active)
+        .style(Style::default().fg(data.settings.colors.default_text))
+        .highlight_style(Style::default().fg(data.settings.colors.selected).bold());
+    frame.render_widget(tabs, frame.area());
+
+    // Saves merge with the other dashboard, so this one says so on every screen
+    let status: Vec<&str> = [(app_state.unsaved, "● unsaved"), (files::shared(), "shared")].into_iter()
+        .filter_map(|(shown, text)| shown.then_some(text))
+        .collect();
+    if !status.is_empty()
+    {
+        let status = Paragraph::new(format!("{} ", status.join(" | ")))
+            .alignment(Alignment::Right)
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(status, Rect { height: 1, ..frame.area() });
+    }
+
+    screens[active].render(frame, screen_area(frame.area()), data, app_state);
+
+    if let Some(conflict) = app_state.conflict.as_ref()
+    {
+        reload::render_conflict(frame, conflict);
+    }
 }
 
 pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect